@@ -3,21 +3,23 @@
 //! # Example
 //!
 //! ```
-//! use std::fs;
 //! use xpidump::XPI;
-//! use zip::ZipArchive;
 //!
-//! let mut archive = ZipArchive::new(
-//!   fs::File::open("tests/fixtures/dev-new.xpi").unwrap()
-//! ).unwrap();
-//! let xpi = XPI::new(&mut archive);
+//! let xpi = XPI::from_path("tests/fixtures/dev-new.xpi").unwrap();
 //!
 //! println!("Add-on ID in the manifest: {}", xpi.manifest.id.unwrap());
 //! // Add-on ID in the manifest: {c208c857-c691-4c69-bfa9-3c2b04f4a0ec}
 //! ```
+//!
+//! An `XPI` can also be constructed from the content of an XPI file with
+//! [`XPI::from_bytes()`], or from an existing
+//! [`ZipArchive`](https://docs.rs/zip/latest/zip/read/struct.ZipArchive.html) with [`XPI::new()`].
 mod xpi;
 
 pub use xpi::*;
 
+#[cfg(feature = "test-support")]
+pub mod test_support;
+
 #[cfg(target_arch = "wasm32")]
 pub mod wasm_bindings;
@@ -1,7 +1,12 @@
-use clap::Parser;
-use std::{fs::File, path::PathBuf};
-use xpidump::XPI;
-use zip::ZipArchive;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::{
+    error, fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use xpidump::{unsign, Inspection, Policy, ScanReport, SigningCredentials, CRX, XPI};
 
 #[derive(clap::ValueEnum, Clone)]
 enum Format {
@@ -11,26 +16,304 @@ enum Format {
 
 /// A simple tool to dump information about XPI files.
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    /// The path to an XPI file
-    file: PathBuf,
-    #[clap(short, long, value_enum, default_value = "text")]
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The path to an XPI (or CRX) file, or `-` to read it from the standard input
+    #[arg(required = true)]
+    file: Option<PathBuf>,
+    #[clap(short, long, value_enum, default_value = "text", global = true)]
     format: Format,
+    /// The path to a JSON policy file to evaluate against the XPI file
+    #[clap(short, long)]
+    policy: Option<PathBuf>,
 }
 
-fn main() {
-    let args = Args::parse();
-    let file = File::open(args.file).unwrap_or_else(|_| panic!("error: failed to open XPI file"));
-    let mut archive =
-        ZipArchive::new(file).unwrap_or_else(|_| panic!("error: failed to read XPI file"));
+#[derive(Subcommand)]
+enum Command {
+    /// Show the differences between two XPI files
+    Diff {
+        /// The path to the old XPI file, or `-` to read it from the standard input
+        old: PathBuf,
+        /// The path to the new XPI file, or `-` to read it from the standard input
+        new: PathBuf,
+    },
+    /// Show the content scripts and host permissions that apply to a URL
+    Matches {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+        /// The URL to match against the content scripts and host permissions
+        url: String,
+    },
+    /// Extract the icon of an XPI file that best fits a size
+    Icon {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+        /// The size of the icon, in pixels
+        #[clap(short, long, default_value_t = 64)]
+        size: u32,
+        /// The path to the image file to write, or `-` to write it to the standard output
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Scan the JavaScript and HTML files of an XPI file for risky constructs
+    Scan {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+    },
+    /// Dump the raw CBOR and ASN.1 structures of the signature files of an XPI file
+    Inspect {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+    },
+    /// Sign an XPI file with a local key, for development and testing purposes
+    Sign {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+        /// The path to the PEM-encoded private key (PKCS#8, P-256)
+        #[clap(short, long)]
+        key: PathBuf,
+        /// The path to the PEM-encoded certificate chain, starting with the end-entity certificate
+        #[clap(short, long)]
+        chain: PathBuf,
+        /// The path to the signed XPI file to write, or `-` to write it to the standard output
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Remove the signature files of an XPI file, keeping the other files untouched
+    Unsign {
+        /// The path to the XPI file, or `-` to read it from the standard input
+        file: PathBuf,
+        /// The path to the unsigned XPI file to write, or `-` to write it to the standard output
+        output: PathBuf,
+    },
+}
+
+/// Represents an error that occurred while running the tool, along with a description of what
+/// the tool was trying to do.
+struct Error {
+    context: String,
+    source: Box<dyn error::Error>,
+}
+
+impl Error {
+    fn new<E: Into<Box<dyn error::Error>>>(context: impl Into<String>, source: E) -> Self {
+        Error {
+            context: context.into(),
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.context)?;
+        let mut source: Option<&dyn error::Error> = Some(self.source.as_ref());
+        while let Some(err) = source {
+            write!(f, "\n  caused by: {}", err)?;
+            source = err.source();
+        }
+        Ok(())
+    }
+}
+
+/// Reads a file, or the standard input when `path` is `-`.
+fn read_input(path: &Path) -> Result<Vec<u8>, Error> {
+    if is_stdio(path) {
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::new("failed to read from stdin", err))?;
+
+        return Ok(bytes);
+    }
+
+    fs::read(path).map_err(|err| Error::new(format!("failed to open `{}`", path.display()), err))
+}
+
+/// Prints a value as JSON or as text, depending on `format`.
+fn print<T: Serialize + fmt::Display + ?Sized>(format: &Format, value: &T) -> Result<(), Error> {
+    let output = match format {
+        Format::Json => serde_json::to_string(value)
+            .map_err(|err| Error::new("failed to serialize to JSON", err))?,
+        Format::Text => value.to_string(),
+    };
+    writeln!(io::stdout(), "{}", output).map_err(|err| Error::new("failed to write to stdout", err))
+}
+
+/// Writes a file, or the standard output when `path` is `-`.
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    if is_stdio(path) {
+        return io::stdout()
+            .write_all(bytes)
+            .map_err(|err| Error::new("failed to write to stdout", err));
+    }
+
+    fs::write(path, bytes)
+        .map_err(|err| Error::new(format!("failed to write `{}`", path.display()), err))
+}
+
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn parse_xpi(path: &Path, bytes: &[u8]) -> Result<XPI, Error> {
+    XPI::from_bytes(bytes)
+        .map_err(|err| Error::new(format!("failed to read XPI file `{}`", path.display()), err))
+}
+
+fn open_xpi(path: &Path) -> Result<XPI, Error> {
+    parse_xpi(path, &read_input(path)?)
+}
+
+fn run(args: Args) -> Result<bool, Error> {
+    let format = &args.format;
+
+    match args.command {
+        Some(Command::Diff { old, new }) => {
+            print(format, &open_xpi(&old)?.diff(&open_xpi(&new)?))?;
+        }
+        Some(Command::Matches { file, url }) => {
+            let matches = open_xpi(&file)?.match_url(&url).ok_or_else(|| {
+                Error::new(
+                    format!("failed to match `{}`", url),
+                    "invalid URL, expected `scheme://host/path`",
+                )
+            })?;
+            print(format, &matches)?;
+        }
+        Some(Command::Icon { file, size, output }) => {
+            let bytes = read_input(&file)?;
+            let xpi = parse_xpi(&file, &bytes)?;
+            let icon = xpi.icon(size).ok_or_else(|| {
+                Error::new(
+                    format!("failed to find an icon in `{}`", file.display()),
+                    "no valid icon declared in the manifest",
+                )
+            })?;
+            let image = icon
+                .read_from_bytes(&bytes)
+                .map_err(|err| Error::new(format!("failed to read icon `{}`", icon.path), err))?;
+
+            write_output(&output, &image)?;
+            if !is_stdio(&output) {
+                print(format, icon)?;
+            }
+        }
+        Some(Command::Scan { file }) => {
+            let report = ScanReport::from_bytes(&read_input(&file)?).map_err(|err| {
+                Error::new(format!("failed to read XPI file `{}`", file.display()), err)
+            })?;
+            print(format, &report)?;
+        }
+        Some(Command::Inspect { file }) => {
+            let inspection = Inspection::from_bytes(&read_input(&file)?).map_err(|err| {
+                Error::new(format!("failed to read XPI file `{}`", file.display()), err)
+            })?;
+            print(format, &inspection)?;
+        }
+        Some(Command::Sign {
+            file,
+            key,
+            chain,
+            output,
+        }) => {
+            let read_pem = |path: &Path| {
+                fs::read_to_string(path)
+                    .map_err(|err| Error::new(format!("failed to read `{}`", path.display()), err))
+            };
+            let credentials = SigningCredentials::from_pem(&read_pem(&key)?, &read_pem(&chain)?)
+                .map_err(|err| Error::new("failed to load signing credentials", err))?;
+            let signed = credentials.sign(&read_input(&file)?).map_err(|err| {
+                Error::new(format!("failed to sign XPI file `{}`", file.display()), err)
+            })?;
+
+            write_output(&output, &signed)?;
+            if !is_stdio(&output) {
+                print(format, &parse_xpi(&output, &signed)?.signatures)?;
+            }
+        }
+        Some(Command::Unsign { file, output }) => {
+            let unsigned = unsign(&read_input(&file)?).map_err(|err| {
+                Error::new(format!("failed to read XPI file `{}`", file.display()), err)
+            })?;
+
+            write_output(&output, &unsigned.bytes)?;
+            if !is_stdio(&output) {
+                print(format, &unsigned)?;
+            }
+        }
+        None => {
+            let file = args
+                .file
+                .as_deref()
+                .expect("FILE is required when there is no subcommand");
+            return dump(file, args.policy.as_deref(), format);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Prints the information about an XPI (or CRX) file, or the result of the evaluation of a
+/// policy against it. Returns whether the policy passed, if any.
+fn dump(file: &Path, policy_file: Option<&Path>, format: &Format) -> Result<bool, Error> {
+    let bytes = read_input(file)?;
+
+    if CRX::is_crx(&bytes) {
+        if policy_file.is_some() {
+            return Err(Error::new(
+                format!("failed to evaluate policy against `{}`", file.display()),
+                "policies can only be evaluated against XPI files",
+            ));
+        }
+
+        let crx = CRX::from_bytes(&bytes).map_err(|err| {
+            Error::new(format!("failed to read CRX file `{}`", file.display()), err)
+        })?;
+        print(format, &crx)?;
+        return Ok(true);
+    }
+
+    let xpi = parse_xpi(file, &bytes)?;
+
+    if let Some(policy_file) = policy_file {
+        let json = fs::read_to_string(policy_file).map_err(|err| {
+            Error::new(
+                format!("failed to read policy file `{}`", policy_file.display()),
+                err,
+            )
+        })?;
+        let policy = Policy::from_json(&json).map_err(|err| {
+            Error::new(
+                format!("failed to parse policy file `{}`", policy_file.display()),
+                err,
+            )
+        })?;
+
+        let report = policy.evaluate(&xpi);
+        print(format, &report)?;
+        return Ok(report.passed());
+    }
+
+    print(format, &xpi)?;
+    Ok(true)
+}
 
-    let xpi = XPI::new(&mut archive);
-    println!(
-        "{}",
-        match args.format {
-            Format::Json => serde_json::to_string(&xpi).unwrap(),
-            Format::Text => xpi.to_string(),
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
         }
-    );
+    }
 }
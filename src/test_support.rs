@@ -0,0 +1,275 @@
+//! Helpers to build certificate hierarchies that mimic the ones used by AMO, so that code relying
+//! on [`Signature::env()`](crate::Signature::env) or [`Signature::kind()`](crate::Signature::kind)
+//! can be tested against every [`Environment`] and [`SignatureKind`] without real signed XPI
+//! files.
+//!
+//! The root certificates do not have the names of the AMO roots, so
+//! [`XPI::verdict()`](crate::XPI::verdict) reports the XPI files signed with them as
+//! [`SignedUntrustedRoot`](crate::VerdictStatus::SignedUntrustedRoot).
+//!
+//! This module requires the `test-support` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use xpidump::{test_support::CertificateHierarchy, Environment, SignatureKind, XPI};
+//! # use std::io::{Cursor, Write};
+//! # let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+//! # writer.start_file("manifest.json", zip::write::FileOptions::default()).unwrap();
+//! # writer.write_all(br#"{ "manifest_version": 2 }"#).unwrap();
+//! # let bytes = writer.finish().unwrap().into_inner();
+//!
+//! let hierarchy =
+//!     CertificateHierarchy::new(Environment::Staging, SignatureKind::System, "test@example.com");
+//! let xpi = XPI::from_bytes(&hierarchy.sign(&bytes).unwrap()).unwrap();
+//!
+//! assert_eq!(Environment::Staging, xpi.signatures.pkcs7.env());
+//! assert_eq!(SignatureKind::System, xpi.signatures.pkcs7.kind());
+//! ```
+use crate::{Environment, SignatureKind, SigningCredentials, SigningError};
+use cms::cert::x509::{
+    attr::AttributeTypeAndValue,
+    der::{
+        asn1::{Any, SetOfVec, UtcTime},
+        DateTime, Tag,
+    },
+    name::{Name, RdnSequence, RelativeDistinguishedName},
+    serial_number::SerialNumber,
+    spki::{ObjectIdentifier, SubjectPublicKeyInfoOwned},
+    time::{Time, Validity},
+    Certificate,
+};
+use const_oid::db::{
+    rfc4519::{COMMON_NAME, COUNTRY_NAME, ORGANIZATIONAL_UNIT_NAME, ORGANIZATION_NAME},
+    rfc5280::ID_KP_CODE_SIGNING,
+};
+use p256::ecdsa::{DerSignature, SigningKey};
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    builder::{Builder, CertificateBuilder, Profile},
+    ext::pkix::ExtendedKeyUsage,
+};
+
+/// Represents a root certificate, an optional intermediate certificate and an end-entity
+/// certificate (with its private key), laid out like the certificates used by AMO in an
+/// environment and for a kind of add-on.
+///
+/// The keys are derived from the names of the certificates and the certificates are valid from
+/// 2024 to 2049, so that the hierarchies (and the signatures made with them) are reproducible.
+pub struct CertificateHierarchy {
+    pub environment: Environment,
+    pub kind: SignatureKind,
+    pub root: Certificate,
+    /// The intermediate certificate, which is absent in the development environment where AMO
+    /// signs add-ons with its root certificate directly.
+    pub intermediate: Option<Certificate>,
+    /// The certificate of the add-on, whose common name is the add-on ID.
+    pub end_entity: Certificate,
+    /// The private key of the end-entity certificate.
+    pub key: SigningKey,
+}
+
+impl CertificateHierarchy {
+    /// Builds the certificate hierarchy of an environment for a kind of add-on.
+    ///
+    /// The names of the CA certificates are recognized by
+    /// [`Signature::env()`](crate::Signature::env) as the ones of the environment.
+    /// [`Environment::Unknown`] builds a hierarchy that it does not recognize, which it reports
+    /// as [`Environment::Production`] like any other certificate.
+    pub fn new(environment: Environment, kind: SignatureKind, addon_id: &str) -> Self {
+        let (root_common_name, intermediate_common_name) = ca_common_names(environment);
+
+        let root_key = derive_key(root_common_name);
+        let root_name = ca_name(root_common_name);
+        let root = build_certificate(Profile::Root, 1, root_name.clone(), &root_key, &root_key);
+
+        let (intermediate, issuer_name, issuer_key) = match intermediate_common_name {
+            Some(common_name) => {
+                let key = derive_key(common_name);
+                let name = ca_name(common_name);
+                let profile = Profile::SubCA {
+                    issuer: root_name,
+                    path_len_constraint: Some(0),
+                };
+                let cert = build_certificate(profile, 2, name.clone(), &key, &root_key);
+                (Some(cert), name, key)
+            }
+            None => (None, root_name, root_key),
+        };
+
+        let key = derive_key(&format!("{}/{}", addon_id, kind_unit(kind)));
+        let end_entity = build_certificate(
+            Profile::Leaf {
+                issuer: issuer_name,
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            3,
+            name(&[
+                (COUNTRY_NAME, "US"),
+                (ORGANIZATION_NAME, "Addons"),
+                (ORGANIZATIONAL_UNIT_NAME, kind_unit(kind)),
+                (COMMON_NAME, addon_id),
+            ]),
+            &key,
+            &issuer_key,
+        );
+
+        CertificateHierarchy {
+            environment,
+            kind,
+            root,
+            intermediate,
+            end_entity,
+            key,
+        }
+    }
+
+    /// Builds the certificate hierarchies of every environment and every kind of add-on.
+    pub fn all(addon_id: &str) -> Vec<Self> {
+        let environments = [
+            Environment::Unknown,
+            Environment::Development,
+            Environment::Staging,
+            Environment::Production,
+        ];
+        let kinds = [
+            SignatureKind::Regular,
+            SignatureKind::Privileged,
+            SignatureKind::System,
+        ];
+
+        environments
+            .iter()
+            .flat_map(|env| kinds.iter().map(|kind| Self::new(*env, *kind, addon_id)))
+            .collect()
+    }
+
+    /// Returns the certificates embedded in the signatures, starting with the end-entity
+    /// certificate. Like AMO, the root certificate is embedded when there is no intermediate
+    /// certificate.
+    pub fn chain(&self) -> Vec<Certificate> {
+        vec![
+            self.end_entity.clone(),
+            self.intermediate.as_ref().unwrap_or(&self.root).clone(),
+        ]
+    }
+
+    /// Returns the credentials to sign XPI files with the end-entity certificate.
+    pub fn credentials(&self) -> SigningCredentials {
+        SigningCredentials::new(self.key.clone(), self.chain())
+            .expect("the key must match the end-entity certificate")
+    }
+
+    /// Signs an XPI file with the end-entity certificate, see [`SigningCredentials::sign()`].
+    pub fn sign(&self, bytes: &[u8]) -> Result<Vec<u8>, SigningError> {
+        self.credentials().sign(bytes)
+    }
+}
+
+/// Returns the common names of the root and intermediate certificates of an environment, which
+/// contain the markers used by [`Signature::env()`](crate::Signature::env) but are not the names
+/// of the AMO certificates.
+fn ca_common_names(environment: Environment) -> (&'static str, Option<&'static str>) {
+    match environment {
+        Environment::Unknown => (
+            "xpidump untrusted root",
+            Some("xpidump untrusted intermediate"),
+        ),
+        // Like AMO, the development root signs the end-entity certificates directly.
+        Environment::Development => ("xpidump test root dev.amo.root.ca", None),
+        Environment::Staging => (
+            "xpidump test root staging",
+            Some("xpidump test intermediate staging"),
+        ),
+        Environment::Production => (
+            "xpidump test root production",
+            Some("xpidump test intermediate production"),
+        ),
+    }
+}
+
+/// Returns the organizational unit of the end-entity certificate of a kind of add-on.
+fn kind_unit(kind: SignatureKind) -> &'static str {
+    match kind {
+        SignatureKind::Regular => "Production",
+        SignatureKind::Privileged => "Mozilla Extensions",
+        SignatureKind::System => "Mozilla Components",
+    }
+}
+
+/// Derives a P-256 key from a label, so that the same certificates are built every time.
+fn derive_key(label: &str) -> SigningKey {
+    let seed = Sha256::digest(format!("xpidump test-support {}", label));
+    SigningKey::from_bytes(&seed).expect("failed to derive key")
+}
+
+fn ca_name(common_name: &str) -> Name {
+    name(&[
+        (COUNTRY_NAME, "US"),
+        (ORGANIZATION_NAME, "xpidump"),
+        (ORGANIZATIONAL_UNIT_NAME, "Testing"),
+        (COMMON_NAME, common_name),
+    ])
+}
+
+fn name(attributes: &[(ObjectIdentifier, &str)]) -> Name {
+    let rdns = attributes
+        .iter()
+        .map(|(oid, value)| {
+            let tag = if *oid == COUNTRY_NAME {
+                Tag::PrintableString
+            } else {
+                Tag::Utf8String
+            };
+            let atv = AttributeTypeAndValue {
+                oid: *oid,
+                value: Any::new(tag, value.as_bytes()).expect("failed to encode name"),
+            };
+            RelativeDistinguishedName(SetOfVec::try_from(vec![atv]).expect("failed to encode name"))
+        })
+        .collect();
+
+    RdnSequence(rdns)
+}
+
+fn build_certificate(
+    profile: Profile,
+    serial_number: u8,
+    subject: Name,
+    key: &SigningKey,
+    issuer_key: &SigningKey,
+) -> Certificate {
+    let is_leaf = matches!(profile, Profile::Leaf { .. });
+    let time = |year| {
+        DateTime::new(year, 1, 1, 0, 0, 0)
+            .and_then(UtcTime::from_date_time)
+            .map(Time::UtcTime)
+            .expect("failed to encode validity")
+    };
+    let validity = Validity {
+        not_before: time(2024),
+        not_after: time(2049),
+    };
+    let public_key = SubjectPublicKeyInfoOwned::from_key(*key.verifying_key())
+        .expect("failed to encode public key");
+
+    let mut builder = CertificateBuilder::new(
+        profile,
+        SerialNumber::new(&[serial_number]).expect("failed to encode serial number"),
+        validity,
+        subject,
+        public_key,
+        issuer_key,
+    )
+    .expect("failed to create certificate");
+    if is_leaf {
+        builder
+            .add_extension(&ExtendedKeyUsage(vec![ID_KP_CODE_SIGNING]))
+            .expect("failed to encode extension");
+    }
+
+    builder
+        .build::<DerSignature>()
+        .expect("failed to build certificate")
+}
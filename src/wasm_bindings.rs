@@ -1,35 +1,381 @@
-use crate::{Environment, SignatureKind, XPI as InnerXPI};
-use std::io::Cursor;
-use wasm_bindgen::prelude::*;
-use zip::ZipArchive;
+use crate::{AddonType, Environment, SignatureKind, XPI as InnerXPI};
+use serde::Serialize;
+use std::error::Error;
+use wasm_bindgen::{prelude::*, JsCast};
 
 // This file contains a thin layer to expose the `xpidump` information in a WASM environment.
 
+// The TypeScript definitions of the plain objects returned by the getters below. They mirror the
+// serialization of the corresponding Rust types (`None` values are serialized as `undefined`),
+// which is checked by `tests/wasm_types_test.rs`.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface ContentScript {
+  matches: string[];
+  exclude_matches: string[];
+  js: string[];
+  css: string[];
+}
+
+export interface WebAccessibleResource {
+  resources: string[];
+  matches: string[];
+}
+
+export type CspContext = "extension_pages" | "sandbox";
+
+export interface Directive {
+  name: string;
+  sources: string[];
+}
+
+export type CspWeaknessKind =
+  | "unsafe_eval"
+  | "unsafe_inline"
+  | "wasm_unsafe_eval"
+  | "remote_script"
+  | "wildcard_source"
+  | "missing_object_src";
+
+export interface CspWeakness {
+  kind: CspWeaknessKind;
+  directive?: string;
+  source?: string;
+}
+
+export interface ContentSecurityPolicy {
+  context: CspContext;
+  policy: string;
+  directives: Directive[];
+  weaknesses: CspWeakness[];
+}
+
+export interface Manifest {
+  present: boolean;
+  manifest_version?: number;
+  name?: string;
+  id?: string;
+  version?: string;
+  admin_install_only?: boolean;
+  permissions: string[];
+  host_permissions: string[];
+  content_scripts: ContentScript[];
+  web_accessible_resources: WebAccessibleResource[];
+  content_security_policy: ContentSecurityPolicy[];
+}
+
+export interface TargetApplication {
+  id?: string;
+  min_version?: string;
+  max_version?: string;
+}
+
+export interface LegacyManifest {
+  has_install_rdf: boolean;
+  has_chrome_manifest: boolean;
+  has_bootstrap_js: boolean;
+  id?: string;
+  version?: string;
+  name?: string;
+  addon_type?: number;
+  bootstrap: boolean;
+  target_applications: TargetApplication[];
+}
+
+export type AddonFormat =
+  | "web_extension"
+  | "bootstrapped"
+  | "legacy_xul"
+  | "theme"
+  | "language_pack"
+  | "dictionary"
+  | "unknown";
+
+export interface ThemeDetails {
+  colors: Record<string, string>;
+  images: Record<string, string[]>;
+}
+
+export interface Dictionary {
+  locale: string;
+  path: string;
+  has_dic_file: boolean;
+  has_aff_file: boolean;
+}
+
+export interface DictionaryDetails {
+  dictionaries: Dictionary[];
+}
+
+export interface LanguagePackDetails {
+  langpack_id: string;
+  languages: string[];
+  strict_min_version?: string;
+  strict_max_version?: string;
+}
+
+export interface SitePermissionDetails {
+  site_permissions: string[];
+  install_origins: string[];
+}
+
+export interface ExtensionAddon {
+  type: "extension";
+}
+
+export interface ThemeAddon extends ThemeDetails {
+  type: "theme";
+}
+
+export interface DictionaryAddon extends DictionaryDetails {
+  type: "dictionary";
+}
+
+export interface LanguagePackAddon extends LanguagePackDetails {
+  type: "language_pack";
+}
+
+export interface SitePermissionAddon extends SitePermissionDetails {
+  type: "site_permission";
+}
+
+export type AddonType =
+  | ExtensionAddon
+  | ThemeAddon
+  | DictionaryAddon
+  | LanguagePackAddon
+  | SitePermissionAddon;
+
+export type RiskLevel = "low" | "medium" | "high" | "critical";
+
+export type PermissionSource = "permissions" | "host_permissions" | "content_scripts";
+
+export type HostScope = "all_hosts" | "domain" | "host" | "files";
+
+export interface ApiPermission {
+  name: string;
+  risk: RiskLevel;
+}
+
+export interface HostPermission {
+  pattern: string;
+  source: PermissionSource;
+  host?: string;
+  scope: HostScope;
+  risk: RiskLevel;
+}
+
+export interface MatchPatternDiagnostic {
+  property: string;
+  pattern: string;
+  message: string;
+}
+
+export interface PermissionAnalysis {
+  api_permissions: ApiPermission[];
+  host_permissions: HostPermission[];
+  warnings: string[];
+  invalid_patterns: MatchPatternDiagnostic[];
+  ignored_host_permissions: MatchPatternDiagnostic[];
+}
+
+export type ReferenceStatus = "found" | "missing" | "case_mismatch";
+
+export interface Reference {
+  property: string;
+  path: string;
+  status: ReferenceStatus;
+  actual_path?: string;
+}
+
+export type IconSource = "icons" | "action" | "browser_action" | "page_action";
+
+export type ImageFormat = "png" | "svg";
+
+export interface Icon {
+  source: IconSource;
+  size?: number;
+  path: string;
+  format?: ImageFormat;
+  width?: number;
+  height?: number;
+  problems: string[];
+}
+
+export interface Certificate {
+  common_name: string;
+  organizational_unit: string;
+  issuer_common_name: string;
+  end_date: string;
+}
+
+export interface Signer {
+  issuer?: string;
+  serial_number?: string;
+  subject_key_identifier?: string;
+  digest_algorithm: string;
+  signature_algorithm: string;
+  signing_time?: string;
+  content_type?: string;
+  message_digest?: string;
+  certificate?: Certificate;
+}
+
+export type CoseQuirk = "kid_array" | "kid_certificate";
+
+export interface Signature {
+  present: boolean;
+  algorithm?: string;
+  certificates: Certificate[];
+  signers: Signer[];
+  quirks: CoseQuirk[];
+}
+
+export type MismatchKind =
+  | "environment"
+  | "common_name"
+  | "organizational_unit"
+  | "kind"
+  | "expiry"
+  | "not_covered";
+
+export interface SignatureMismatch {
+  kind: MismatchKind;
+  message: string;
+}
+
+export interface Signatures {
+  pkcs7: Signature;
+  cose: Signature;
+  mismatches: SignatureMismatch[];
+}
+
+export type VerdictStatus =
+  | "unsigned"
+  | "tampered"
+  | "signed_untrusted_root"
+  | "claims_development_root"
+  | "claims_staging_root"
+  | "claims_production_root";
+
+export interface Verdict {
+  status: VerdictStatus;
+  root?: string;
+  privileged: boolean;
+  system: boolean;
+  enterprise: boolean;
+  recommended: boolean;
+  reasons: string[];
+}
+
+export type RecommendationState = "recommended" | "recommended-android" | "line" | "verified";
+
+export interface RecommendationValidity {
+  not_before: string;
+  not_after: string;
+}
+
+export interface Recommendation {
+  schema_version: number;
+  addon_id: string;
+  states: RecommendationState[];
+  validity: RecommendationValidity;
+}
+
+export interface Entry {
+  name: string;
+  size: number;
+  sha256: string;
+  is_source_map: boolean;
+}
+
+export interface XPIData {
+  manifest: Manifest;
+  legacy_manifest?: LegacyManifest;
+  format: AddonFormat;
+  addon_type?: AddonType;
+  permissions: PermissionAnalysis;
+  references: Reference[];
+  icons: Icon[];
+  signatures: Signatures;
+  recommendation?: Recommendation;
+  entries: Entry[];
+  verdict: Verdict;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "AddonType | undefined")]
+    pub type AddonTypeDetails;
+    #[wasm_bindgen(typescript_type = "Certificate[]")]
+    pub type CertificateList;
+    #[wasm_bindgen(typescript_type = "CoseQuirk[]")]
+    pub type CoseQuirkList;
+    #[wasm_bindgen(typescript_type = "Entry[]")]
+    pub type EntryList;
+    #[wasm_bindgen(typescript_type = "Icon[]")]
+    pub type IconList;
+    #[wasm_bindgen(typescript_type = "Manifest")]
+    pub type Manifest;
+    #[wasm_bindgen(typescript_type = "RecommendationState[]")]
+    pub type RecommendationStateList;
+    #[wasm_bindgen(typescript_type = "RecommendationValidity | undefined")]
+    pub type RecommendationValidity;
+    #[wasm_bindgen(typescript_type = "SignatureMismatch[]")]
+    pub type SignatureMismatchList;
+    #[wasm_bindgen(typescript_type = "Signatures")]
+    pub type Signatures;
+    #[wasm_bindgen(typescript_type = "Verdict")]
+    pub type Verdict;
+    #[wasm_bindgen(typescript_type = "XPIData")]
+    pub type XPIData;
+}
+
+/// Converts a value to a plain JS object and casts it to one of the TypeScript types above. Maps
+/// are converted to plain objects too, like in the JSON output.
+fn to_typed<T: Serialize + ?Sized, R: JsCast>(value: &T) -> Result<R, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+
+    Ok(value.serialize(&serializer)?.unchecked_into())
+}
+
 #[wasm_bindgen]
 pub struct XPI {
     xpi: InnerXPI,
+    // The content of the XPI file, used to read files on demand (e.g. icons).
+    data: Vec<u8>,
 }
 
 #[wasm_bindgen]
 impl XPI {
+    /// Constructs a new `XPI` from the bytes of a file. This throws an `Error` when the file
+    /// cannot be read as a ZIP archive.
     #[wasm_bindgen(constructor)]
-    pub fn new(data: Vec<u8>) -> XPI {
-        let reader = Cursor::new(data);
-        let mut zip_archive = ZipArchive::new(reader).unwrap();
+    pub fn new(data: Vec<u8>) -> Result<XPI, JsError> {
+        let xpi = InnerXPI::from_bytes(&data).map_err(|err| match err.source() {
+            Some(cause) => JsError::new(&format!("{}: {}", err, cause)),
+            None => JsError::new(&err.to_string()),
+        })?;
 
-        XPI {
-            xpi: InnerXPI::new(&mut zip_archive),
-        }
+        Ok(XPI { xpi, data })
     }
 
+    /// Returns all the information about the XPI file as a plain object.
     #[wasm_bindgen]
-    pub fn to_js(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.xpi).unwrap()
+    pub fn to_js(&self) -> Result<XPIData, JsError> {
+        to_typed(&self.xpi)
+    }
+
+    /// Returns all the information about the XPI file as a JSON string, like the CLI does.
+    #[wasm_bindgen]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.xpi)?)
     }
 
     #[wasm_bindgen(getter)]
-    pub fn manifest(&self) -> JsValue {
-        serde_wasm_bindgen::to_value(&self.xpi.manifest).unwrap()
+    pub fn manifest(&self) -> Result<Manifest, JsError> {
+        to_typed(&self.xpi.manifest)
     }
 
     #[wasm_bindgen(getter)]
@@ -58,9 +404,9 @@ impl XPI {
             .signatures
             .pkcs7
             .algorithm
-            .as_deref()
-            .unwrap_or("")
-            .to_owned()
+            .as_ref()
+            .map(|algorithm| algorithm.to_string())
+            .unwrap_or_default()
     }
 
     #[wasm_bindgen(getter)]
@@ -83,9 +429,99 @@ impl XPI {
             .signatures
             .cose
             .algorithm
-            .as_deref()
-            .unwrap_or("")
-            .to_owned()
+            .as_ref()
+            .map(|algorithm| algorithm.to_string())
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn addon_type(&self) -> String {
+        match self.xpi.addon_type {
+            Some(AddonType::Extension) => "extension".to_string(),
+            Some(AddonType::Theme(_)) => "theme".to_string(),
+            Some(AddonType::Dictionary(_)) => "dictionary".to_string(),
+            Some(AddonType::LanguagePack(_)) => "language_pack".to_string(),
+            Some(AddonType::SitePermission(_)) => "site_permission".to_string(),
+            None => "".to_string(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn addon_type_details(&self) -> Result<AddonTypeDetails, JsError> {
+        to_typed(&self.xpi.addon_type)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn icons(&self) -> Result<IconList, JsError> {
+        to_typed(&self.xpi.icons)
+    }
+
+    /// Returns the content of the icon that best fits `size` (in pixels), or `undefined` when
+    /// there is no valid icon.
+    #[wasm_bindgen]
+    pub fn icon(&self, size: u32) -> Option<Vec<u8>> {
+        self.xpi.icon(size)?.read_from_bytes(&self.data).ok()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn verdict(&self) -> Result<Verdict, JsError> {
+        to_typed(self.xpi.verdict())
+    }
+
+    /// Returns the PKCS#7 and COSE signatures, along with their disagreements.
+    #[wasm_bindgen(getter)]
+    pub fn signatures(&self) -> Result<Signatures, JsError> {
+        to_typed(&self.xpi.signatures)
+    }
+
+    /// Returns the certificates of the PKCS#7 signature, from the root to the end-entity.
+    #[wasm_bindgen(getter)]
+    pub fn pkcs7_certificates(&self) -> Result<CertificateList, JsError> {
+        to_typed(&self.xpi.signatures.pkcs7.certificates)
+    }
+
+    /// Returns the certificates of the COSE signature, from the root to the end-entity.
+    #[wasm_bindgen(getter)]
+    pub fn cose_certificates(&self) -> Result<CertificateList, JsError> {
+        to_typed(&self.xpi.signatures.cose.certificates)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cose_quirks(&self) -> Result<CoseQuirkList, JsError> {
+        to_typed(&self.xpi.signatures.cose.quirks)
+    }
+
+    /// Returns the disagreements between the PKCS#7 and COSE signatures.
+    #[wasm_bindgen(getter)]
+    pub fn mismatches(&self) -> Result<SignatureMismatchList, JsError> {
+        to_typed(&self.xpi.signatures.mismatches)
+    }
+
+    /// Returns the recommendation states, which are empty when the XPI file is not recommended.
+    #[wasm_bindgen(getter)]
+    pub fn recommendation_states(&self) -> Result<RecommendationStateList, JsError> {
+        to_typed(
+            self.xpi
+                .recommendation
+                .as_ref()
+                .map_or(&[][..], |rec| &rec.states[..]),
+        )
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn recommendation_validity(&self) -> Result<RecommendationValidity, JsError> {
+        to_typed(&self.xpi.recommendation.as_ref().map(|rec| &rec.validity))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_recommended(&self) -> bool {
+        self.xpi.is_recommended()
+    }
+
+    /// Returns the files stored in the XPI file, with their sizes and SHA-256 hashes.
+    #[wasm_bindgen(getter)]
+    pub fn entries(&self) -> Result<EntryList, JsError> {
+        to_typed(&self.xpi.entries)
     }
 
     #[wasm_bindgen(getter)]
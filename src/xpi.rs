@@ -1,13 +1,59 @@
+mod addon_type;
+mod algorithms;
+mod consistency;
 mod cose_ish;
+mod crx;
+mod csp;
+mod diff;
+mod entries;
+mod error;
+mod icons;
+mod inspect;
+mod legacy;
+mod libraries;
 mod manifest;
+mod match_pattern;
+mod permissions;
+mod policy;
+mod references;
+mod scanner;
 mod signatures;
+#[cfg(feature = "signing")]
+mod signing;
+mod source_maps;
+mod unsign;
+mod verdict;
+mod wildcard;
 
 use serde::{Deserialize, Serialize};
-use std::{fmt, io};
+use std::{fmt, fs::File, io, io::Cursor, path::Path};
 use zip::ZipArchive;
 
+pub use addon_type::*;
+pub use algorithms::*;
+pub use consistency::*;
+pub use cose_ish::CoseQuirk;
+pub use crx::*;
+pub use csp::*;
+pub use diff::*;
+pub use entries::*;
+pub use error::*;
+pub use icons::*;
+pub use inspect::*;
+pub use legacy::*;
+pub use libraries::*;
 pub use manifest::*;
+pub use match_pattern::*;
+pub use permissions::*;
+pub use policy::*;
+pub use references::*;
+pub use scanner::*;
 pub use signatures::*;
+#[cfg(feature = "signing")]
+pub use signing::*;
+pub use source_maps::*;
+pub use unsign::*;
+pub use verdict::*;
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 /// Represents the recommendation state values.
@@ -49,10 +95,27 @@ pub struct Recommendation {
 pub struct XPI {
     /// Information about the `manifest.json` file.
     pub manifest: Manifest,
+    /// Information about the `install.rdf`, `bootstrap.js` and `chrome.manifest` files used by
+    /// legacy add-ons, if any.
+    pub legacy_manifest: Option<LegacyManifest>,
+    /// The format of the add-on.
+    pub format: AddonFormat,
+    /// The type of the add-on when it is a WebExtension.
+    pub addon_type: Option<AddonType>,
+    /// The analysis of the permissions declared in the `manifest.json` file.
+    pub permissions: PermissionAnalysis,
+    /// The files referenced in the `manifest.json` file, resolved against the files stored in the
+    /// XPI file.
+    pub references: Vec<Reference>,
+    /// The icons declared in the `manifest.json` file.
+    pub icons: Vec<Icon>,
     /// Information about the signatures found in the XPI.
     pub signatures: Signatures,
     /// The recommendation state found in the XPI file, if any.
     pub recommendation: Option<Recommendation>,
+    /// The files stored in the XPI file.
+    pub entries: Vec<Entry>,
+    verdict: Verdict,
 }
 
 impl XPI {
@@ -64,13 +127,71 @@ impl XPI {
             recommendation = serde_json::from_reader::<_, Recommendation>(rec_file).ok();
         }
 
+        let manifest = Manifest::parse(archive);
+        let legacy_manifest = LegacyManifest::parse(archive);
+        let format = if manifest.exists() {
+            AddonFormat::WebExtension
+        } else if let Some(legacy) = &legacy_manifest {
+            legacy.format()
+        } else {
+            AddonFormat::Unknown
+        };
+
+        let entries = Entry::parse_all(archive);
+        let addon_type = AddonType::detect(&manifest, &entries);
+        let permissions = PermissionAnalysis::new(&manifest);
+        let references = Reference::check_all(&manifest, &entries);
+        let icons = Icon::parse_all(&manifest, archive);
+
+        let signatures = Signatures::parse(archive);
+        let verdict = Verdict::new(
+            &signatures,
+            check_integrity(archive, &signatures),
+            signatures.has_signatures() && manifest.has_enterprise_flag(),
+            signatures.has_signatures() && recommendation.is_some(),
+        );
+
         XPI {
-            manifest: Manifest::parse(archive),
-            signatures: Signatures::parse(archive),
+            manifest,
+            legacy_manifest,
+            format,
+            addon_type,
+            permissions,
+            references,
+            icons,
+            signatures,
             recommendation,
+            entries,
+            verdict,
         }
     }
 
+    /// Constructs a new `XPI` from the content of an XPI file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<XPI, Error> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+        Ok(XPI::new(&mut archive))
+    }
+
+    /// Constructs a new `XPI` from the path to an XPI file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<XPI, Error> {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+
+        Ok(XPI::new(&mut archive))
+    }
+
+    /// Returns the most appropriate icon for the given size (in pixels), if any. The content of
+    /// the image can be read with [`Icon::read`].
+    pub fn icon(&self, size: u32) -> Option<&Icon> {
+        find_icon(&self.icons, size)
+    }
+
+    /// Returns a summary of the signature of the XPI: whether it is signed, with which root
+    /// certificate, and as what kind of add-on.
+    pub fn verdict(&self) -> &Verdict {
+        &self.verdict
+    }
+
     /// Whether the XPI is a _recommended_ add-on, i.e. it "looks" signed (i.e. it embeds
     /// signature files) and it has a recommendation state.
     pub fn is_recommended(&self) -> bool {
@@ -92,10 +213,54 @@ impl fmt::Display for XPI {
             "NONE".to_owned()
         };
 
+        write!(f, "{}\n\nFORMAT:\n  {}", self.manifest, self.format)?;
+        if let Some(addon_type) = &self.addon_type {
+            write!(f, "\n\nTYPE:\n  {}", addon_type)?;
+        }
+        if self.manifest.exists() {
+            write!(f, "\n\n{}", self.permissions)?;
+
+            write!(f, "\n\nCONTENT SECURITY POLICY:")?;
+            if self.manifest.content_security_policy.is_empty() {
+                write!(f, "\n  NONE")?;
+            }
+            for csp in &self.manifest.content_security_policy {
+                write!(f, "\n  {}", csp)?;
+            }
+
+            let found = self
+                .references
+                .iter()
+                .filter(|reference| reference.status == ReferenceStatus::Found)
+                .count();
+            write!(
+                f,
+                "\n\nREFERENCES:\n  {} of {} referenced file(s) found",
+                found,
+                self.references.len()
+            )?;
+            for reference in &self.references {
+                if reference.status != ReferenceStatus::Found {
+                    write!(f, "\n   └── {}", reference)?;
+                }
+            }
+
+            write!(f, "\n\nICONS:")?;
+            if self.icons.is_empty() {
+                write!(f, "\n  NONE")?;
+            }
+            for icon in &self.icons {
+                write!(f, "\n  {}", icon)?;
+            }
+        }
+        if let Some(legacy_manifest) = &self.legacy_manifest {
+            write!(f, "\n\n{}", legacy_manifest)?;
+        }
+
         write!(
             f,
-            "{}\n\nRECOMMENDATION:\n  {}\n\n{}",
-            self.manifest, recommendation, self.signatures,
+            "\n\n{}\n\nRECOMMENDATION:\n  {}\n\n{}",
+            self.verdict, recommendation, self.signatures,
         )
     }
 }
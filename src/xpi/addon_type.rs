@@ -0,0 +1,224 @@
+use super::{manifest::string_array, Entry, Manifest};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the information specific to a static theme.
+pub struct ThemeDetails {
+    /// The colors declared in `theme.colors`, indexed by name. Colors declared as RGB(A) arrays
+    /// are converted to their JSON representation.
+    pub colors: BTreeMap<String, String>,
+    /// The images declared in `theme.images`, indexed by name.
+    pub images: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a dictionary declared in the `dictionaries` property of the manifest.
+pub struct Dictionary {
+    /// The locale of the dictionary (e.g., `en-US`).
+    pub locale: String,
+    /// The path to the `.dic` file.
+    pub path: String,
+    /// Whether the `.dic` file exists in the XPI.
+    pub has_dic_file: bool,
+    /// Whether the `.aff` file (which must have the same name as the `.dic` file) exists in the
+    /// XPI.
+    pub has_aff_file: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the information specific to a dictionary.
+pub struct DictionaryDetails {
+    pub dictionaries: Vec<Dictionary>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the information specific to a language pack.
+pub struct LanguagePackDetails {
+    /// The value of the `langpack_id` property.
+    pub langpack_id: String,
+    /// The languages declared in the `languages` property.
+    pub languages: Vec<String>,
+    /// The minimum version of Firefox supported by the language pack, if any.
+    pub strict_min_version: Option<String>,
+    /// The maximum version of Firefox supported by the language pack, if any.
+    pub strict_max_version: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the information specific to a site permission add-on.
+pub struct SitePermissionDetails {
+    /// The permissions declared in the `site_permissions` property (e.g., `midi`).
+    pub site_permissions: Vec<String>,
+    /// The origins declared in the `install_origins` property.
+    pub install_origins: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// Represents the type of a WebExtension, as determined by the properties of its manifest.
+pub enum AddonType {
+    /// A regular extension.
+    Extension,
+    /// A static theme (`theme` property).
+    Theme(ThemeDetails),
+    /// A dictionary (`dictionaries` property).
+    Dictionary(DictionaryDetails),
+    /// A language pack (`langpack_id` property).
+    LanguagePack(LanguagePackDetails),
+    /// A site permission add-on (`site_permissions` property).
+    SitePermission(SitePermissionDetails),
+}
+
+impl AddonType {
+    /// Determines the type of the add-on from its manifest. The entries of the XPI are used to
+    /// verify the presence of some of the files referenced in the manifest.
+    pub(crate) fn detect(manifest: &Manifest, entries: &[Entry]) -> Option<AddonType> {
+        if !manifest.exists() {
+            return None;
+        }
+
+        let data = &manifest.data;
+
+        if let Some(theme) = data.get("theme") {
+            let mut details = ThemeDetails::default();
+            if let Some(colors) = theme.get("colors").and_then(|v| v.as_object()) {
+                for (name, value) in colors {
+                    details.colors.insert(
+                        name.to_owned(),
+                        match value {
+                            Value::String(s) => s.to_owned(),
+                            value => value.to_string(),
+                        },
+                    );
+                }
+            }
+            if let Some(images) = theme.get("images").and_then(|v| v.as_object()) {
+                for (name, value) in images {
+                    let paths = match value {
+                        Value::String(s) => vec![s.to_owned()],
+                        value => string_array(Some(value)),
+                    };
+                    details.images.insert(name.to_owned(), paths);
+                }
+            }
+
+            return Some(AddonType::Theme(details));
+        }
+
+        if let Some(dictionaries) = data.get("dictionaries").and_then(|v| v.as_object()) {
+            let has_entry = |path: &str| {
+                let path = path.trim_start_matches('/');
+                entries.iter().any(|entry| entry.name == path)
+            };
+
+            let dictionaries = dictionaries
+                .iter()
+                .filter_map(|(locale, path)| {
+                    let path = path.as_str()?;
+                    let aff_path = match path.strip_suffix(".dic") {
+                        Some(base) => format!("{}.aff", base),
+                        None => format!("{}.aff", path),
+                    };
+
+                    Some(Dictionary {
+                        locale: locale.to_owned(),
+                        path: path.to_owned(),
+                        has_dic_file: path.ends_with(".dic") && has_entry(path),
+                        has_aff_file: has_entry(&aff_path),
+                    })
+                })
+                .collect();
+
+            return Some(AddonType::Dictionary(DictionaryDetails { dictionaries }));
+        }
+
+        if let Some(langpack_id) = data.get("langpack_id").and_then(|v| v.as_str()) {
+            let gecko = data
+                .get("browser_specific_settings")
+                .or(data.get("applications"))
+                .and_then(|bss| bss.get("gecko"));
+            let version = |name: &str| {
+                gecko
+                    .and_then(|gecko| gecko.get(name))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_owned())
+            };
+
+            return Some(AddonType::LanguagePack(LanguagePackDetails {
+                langpack_id: langpack_id.to_owned(),
+                languages: data
+                    .get("languages")
+                    .and_then(|v| v.as_object())
+                    .map(|languages| languages.keys().cloned().collect())
+                    .unwrap_or_default(),
+                strict_min_version: version("strict_min_version"),
+                strict_max_version: version("strict_max_version"),
+            }));
+        }
+
+        if data.get("site_permissions").is_some() {
+            return Some(AddonType::SitePermission(SitePermissionDetails {
+                site_permissions: string_array(data.get("site_permissions")),
+                install_origins: string_array(data.get("install_origins")),
+            }));
+        }
+
+        Some(AddonType::Extension)
+    }
+}
+
+impl fmt::Display for AddonType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddonType::Extension => write!(f, "EXTENSION"),
+            AddonType::Theme(details) => {
+                write!(f, "THEME")?;
+                for (name, color) in &details.colors {
+                    write!(f, "\n   └── Color {}: {}", name, color)?;
+                }
+                for (name, paths) in &details.images {
+                    write!(f, "\n   └── Image {}: {}", name, paths.join(", "))?;
+                }
+                Ok(())
+            }
+            AddonType::Dictionary(details) => {
+                write!(f, "DICTIONARY")?;
+                for dictionary in &details.dictionaries {
+                    write!(
+                        f,
+                        "\n   └── {}: {} (.dic: {}, .aff: {})",
+                        dictionary.locale,
+                        dictionary.path,
+                        if dictionary.has_dic_file {
+                            "PRESENT"
+                        } else {
+                            "ABSENT"
+                        },
+                        if dictionary.has_aff_file {
+                            "PRESENT"
+                        } else {
+                            "ABSENT"
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+            AddonType::LanguagePack(details) => write!(
+                f,
+                "LANGUAGE PACK\n   └── ID       : {}\n   └── Languages: {}\n   └── Versions : {} - {}",
+                details.langpack_id,
+                details.languages.join(", "),
+                details.strict_min_version.as_deref().unwrap_or("N/A"),
+                details.strict_max_version.as_deref().unwrap_or("N/A"),
+            ),
+            AddonType::SitePermission(details) => write!(
+                f,
+                "SITE PERMISSION\n   └── Permissions    : {}\n   └── Install Origins: {}",
+                details.site_permissions.join(", "),
+                details.install_origins.join(", "),
+            ),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use cms::cert::x509::{
+    der::{
+        asn1::{Any, ContextSpecific, ObjectIdentifier},
+        SliceReader, Tag, TagNumber, Tagged,
+    },
+    spki::AlgorithmIdentifierOwned,
+};
+use const_oid::db::{
+    rfc5912::{
+        ECDSA_WITH_SHA_256, ECDSA_WITH_SHA_384, ECDSA_WITH_SHA_512, ID_RSASSA_PSS, ID_SHA_1,
+        ID_SHA_256, ID_SHA_384, ID_SHA_512, RSA_ENCRYPTION, SHA_1_WITH_RSA_ENCRYPTION,
+        SHA_256_WITH_RSA_ENCRYPTION, SHA_384_WITH_RSA_ENCRYPTION, SHA_512_WITH_RSA_ENCRYPTION,
+    },
+    rfc8410::{ID_ED_25519, ID_ED_448},
+};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents the digest algorithms used in signatures.
+pub enum DigestAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+    /// An algorithm that is not supported, with its OID.
+    Unknown(String),
+}
+
+impl DigestAlgorithm {
+    pub fn from_oid(oid: &ObjectIdentifier) -> Self {
+        match *oid {
+            ID_SHA_1 => DigestAlgorithm::Sha1,
+            ID_SHA_256 => DigestAlgorithm::Sha256,
+            ID_SHA_384 => DigestAlgorithm::Sha384,
+            ID_SHA_512 => DigestAlgorithm::Sha512,
+            _ => DigestAlgorithm::Unknown(oid.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestAlgorithm::Sha1 => write!(f, "SHA-1"),
+            DigestAlgorithm::Sha256 => write!(f, "SHA-256"),
+            DigestAlgorithm::Sha384 => write!(f, "SHA-384"),
+            DigestAlgorithm::Sha512 => write!(f, "SHA-512"),
+            DigestAlgorithm::Unknown(oid) => write!(f, "{}", oid),
+        }
+    }
+}
+
+impl Serialize for DigestAlgorithm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents the signature algorithms, identified by OIDs in PKCS#7 signatures and by integers
+/// in COSE signatures. The names are the ones registered for COSE (e.g. `ES256`), algorithms
+/// without such a name are reported as unknown.
+pub enum SignatureAlgorithm {
+    RsaPkcs1Sha1,
+    RsaPkcs1Sha256,
+    RsaPkcs1Sha384,
+    RsaPkcs1Sha512,
+    RsaPssSha256,
+    RsaPssSha384,
+    RsaPssSha512,
+    EcdsaSha256,
+    EcdsaSha384,
+    EcdsaSha512,
+    EdDsa,
+    /// An algorithm of a PKCS#7 signature that is not supported, with its OID.
+    UnknownOid(String),
+    /// An algorithm of a COSE signature that is not supported, with its COSE identifier.
+    UnknownCose(i64),
+}
+
+impl SignatureAlgorithm {
+    /// Returns the algorithm of a PKCS#7 signer. The digest algorithm of the signer is used when
+    /// the signature algorithm is `rsaEncryption`, and the digest algorithm of RSASSA-PSS is read
+    /// from its parameters.
+    pub fn from_algorithm_identifier(
+        identifier: &AlgorithmIdentifierOwned,
+        digest_algorithm: &DigestAlgorithm,
+    ) -> Self {
+        let digest_algorithm = match identifier.oid {
+            RSA_ENCRYPTION => Some(digest_algorithm.clone()),
+            ID_RSASSA_PSS => pss_digest_algorithm(identifier.parameters.as_ref()),
+            _ => None,
+        };
+
+        match (identifier.oid, digest_algorithm) {
+            (RSA_ENCRYPTION, Some(DigestAlgorithm::Sha1)) | (SHA_1_WITH_RSA_ENCRYPTION, _) => {
+                SignatureAlgorithm::RsaPkcs1Sha1
+            }
+            (RSA_ENCRYPTION, Some(DigestAlgorithm::Sha256)) | (SHA_256_WITH_RSA_ENCRYPTION, _) => {
+                SignatureAlgorithm::RsaPkcs1Sha256
+            }
+            (RSA_ENCRYPTION, Some(DigestAlgorithm::Sha384)) | (SHA_384_WITH_RSA_ENCRYPTION, _) => {
+                SignatureAlgorithm::RsaPkcs1Sha384
+            }
+            (RSA_ENCRYPTION, Some(DigestAlgorithm::Sha512)) | (SHA_512_WITH_RSA_ENCRYPTION, _) => {
+                SignatureAlgorithm::RsaPkcs1Sha512
+            }
+            (ID_RSASSA_PSS, Some(DigestAlgorithm::Sha256)) => SignatureAlgorithm::RsaPssSha256,
+            (ID_RSASSA_PSS, Some(DigestAlgorithm::Sha384)) => SignatureAlgorithm::RsaPssSha384,
+            (ID_RSASSA_PSS, Some(DigestAlgorithm::Sha512)) => SignatureAlgorithm::RsaPssSha512,
+            (ECDSA_WITH_SHA_256, _) => SignatureAlgorithm::EcdsaSha256,
+            (ECDSA_WITH_SHA_384, _) => SignatureAlgorithm::EcdsaSha384,
+            (ECDSA_WITH_SHA_512, _) => SignatureAlgorithm::EcdsaSha512,
+            (ID_ED_25519 | ID_ED_448, _) => SignatureAlgorithm::EdDsa,
+            (oid, _) => SignatureAlgorithm::UnknownOid(oid.to_string()),
+        }
+    }
+
+    /// Returns the algorithm for an identifier of the IANA "COSE Algorithms" registry.
+    pub fn from_cose(id: i64) -> Self {
+        match id {
+            -7 => SignatureAlgorithm::EcdsaSha256,
+            -35 => SignatureAlgorithm::EcdsaSha384,
+            -36 => SignatureAlgorithm::EcdsaSha512,
+            -8 => SignatureAlgorithm::EdDsa,
+            -37 => SignatureAlgorithm::RsaPssSha256,
+            -38 => SignatureAlgorithm::RsaPssSha384,
+            -39 => SignatureAlgorithm::RsaPssSha512,
+            -257 => SignatureAlgorithm::RsaPkcs1Sha256,
+            -258 => SignatureAlgorithm::RsaPkcs1Sha384,
+            -259 => SignatureAlgorithm::RsaPkcs1Sha512,
+            -65535 => SignatureAlgorithm::RsaPkcs1Sha1,
+            _ => SignatureAlgorithm::UnknownCose(id),
+        }
+    }
+
+    /// Returns the identifier of the algorithm in the IANA "COSE Algorithms" registry, if any.
+    pub fn cose_id(&self) -> Option<i64> {
+        match self {
+            SignatureAlgorithm::EcdsaSha256 => Some(-7),
+            SignatureAlgorithm::EcdsaSha384 => Some(-35),
+            SignatureAlgorithm::EcdsaSha512 => Some(-36),
+            SignatureAlgorithm::EdDsa => Some(-8),
+            SignatureAlgorithm::RsaPssSha256 => Some(-37),
+            SignatureAlgorithm::RsaPssSha384 => Some(-38),
+            SignatureAlgorithm::RsaPssSha512 => Some(-39),
+            SignatureAlgorithm::RsaPkcs1Sha256 => Some(-257),
+            SignatureAlgorithm::RsaPkcs1Sha384 => Some(-258),
+            SignatureAlgorithm::RsaPkcs1Sha512 => Some(-259),
+            SignatureAlgorithm::RsaPkcs1Sha1 => Some(-65535),
+            SignatureAlgorithm::UnknownCose(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureAlgorithm::RsaPkcs1Sha1 => write!(f, "RS1"),
+            SignatureAlgorithm::RsaPkcs1Sha256 => write!(f, "RS256"),
+            SignatureAlgorithm::RsaPkcs1Sha384 => write!(f, "RS384"),
+            SignatureAlgorithm::RsaPkcs1Sha512 => write!(f, "RS512"),
+            SignatureAlgorithm::RsaPssSha256 => write!(f, "PS256"),
+            SignatureAlgorithm::RsaPssSha384 => write!(f, "PS384"),
+            SignatureAlgorithm::RsaPssSha512 => write!(f, "PS512"),
+            SignatureAlgorithm::EcdsaSha256 => write!(f, "ES256"),
+            SignatureAlgorithm::EcdsaSha384 => write!(f, "ES384"),
+            SignatureAlgorithm::EcdsaSha512 => write!(f, "ES512"),
+            SignatureAlgorithm::EdDsa => write!(f, "EdDSA"),
+            SignatureAlgorithm::UnknownOid(oid) => write!(f, "{}", oid),
+            SignatureAlgorithm::UnknownCose(id) => write!(f, "COSE({})", id),
+        }
+    }
+}
+
+impl Serialize for SignatureAlgorithm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Returns the digest algorithm of the RSASSA-PSS parameters, i.e. the `hashAlgorithm` field,
+/// which defaults to SHA-1 (RFC 4055).
+fn pss_digest_algorithm(parameters: Option<&Any>) -> Option<DigestAlgorithm> {
+    let Some(parameters) = parameters else {
+        return Some(DigestAlgorithm::Sha1);
+    };
+    if parameters.tag() != Tag::Sequence {
+        return None;
+    }
+
+    let mut reader = SliceReader::new(parameters.value()).ok()?;
+    let hash_algorithm =
+        ContextSpecific::<AlgorithmIdentifierOwned>::decode_explicit(&mut reader, TagNumber::N0)
+            .ok()?;
+
+    Some(hash_algorithm.map_or(DigestAlgorithm::Sha1, |field| {
+        DigestAlgorithm::from_oid(&field.value.oid)
+    }))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents the algorithm reported for a [`Signature`](`crate::Signature`): the digest
+/// algorithm for PKCS#7 signatures and the signature algorithm for COSE signatures.
+pub enum Algorithm {
+    Digest(DigestAlgorithm),
+    Signature(SignatureAlgorithm),
+    /// The signature does not specify its algorithm, e.g. a COSE signature without `alg` header.
+    Unknown,
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Algorithm::Digest(algorithm) => write!(f, "{}", algorithm),
+            Algorithm::Signature(algorithm) => write!(f, "{}", algorithm),
+            Algorithm::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
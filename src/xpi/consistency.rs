@@ -0,0 +1,114 @@
+use super::{
+    signatures::{COSE_FILES, PKCS7_MANIFEST_FILE},
+    verdict::{parse_manifest, read_file},
+    CertificateInfo, Signature,
+};
+use serde::Serialize;
+use std::{fmt, io};
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the properties compared between the PKCS#7 and COSE signatures.
+pub enum MismatchKind {
+    /// The environment (development, staging or production) of the certificate chains.
+    Environment,
+    /// The common name of the end-entity certificates, i.e. the add-on ID.
+    CommonName,
+    /// The organizational unit of the end-entity certificates.
+    OrganizationalUnit,
+    /// The kind of signature (regular, privileged or system) of the end-entity certificates.
+    Kind,
+    /// The end date of the end-entity certificates.
+    Expiry,
+    /// A COSE file is not listed in the PKCS#7 manifest (`META-INF/manifest.mf`).
+    NotCovered,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a disagreement between the PKCS#7 and COSE signatures of a dual-signed XPI.
+pub struct SignatureMismatch {
+    pub kind: MismatchKind,
+    pub message: String,
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Compares the PKCS#7 and COSE signatures when both are present. Firefox verifies the COSE
+/// signature when there is one, so both signatures should describe the same add-on.
+pub(crate) fn check_consistency<R: io::Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    pkcs7: &Signature,
+    cose: &Signature,
+) -> Vec<SignatureMismatch> {
+    if !pkcs7.exists() || !cose.exists() {
+        return vec![];
+    }
+
+    let mut mismatches = vec![];
+    let mut compare = |kind: MismatchKind, name: &str, pkcs7: String, cose: String| {
+        if pkcs7 != cose {
+            mismatches.push(SignatureMismatch {
+                kind,
+                message: format!("{}: PKCS7 is {}, COSE is {}", name, pkcs7, cose),
+            });
+        }
+    };
+
+    compare(
+        MismatchKind::Environment,
+        "environment",
+        pkcs7.env().to_string(),
+        cose.env().to_string(),
+    );
+    let end_entity = |signature: &Signature, field: fn(&CertificateInfo) -> String| {
+        signature.end_entity().map_or("N/A".to_owned(), field)
+    };
+    compare(
+        MismatchKind::CommonName,
+        "common name",
+        end_entity(pkcs7, |cert| cert.common_name.to_owned()),
+        end_entity(cose, |cert| cert.common_name.to_owned()),
+    );
+    compare(
+        MismatchKind::OrganizationalUnit,
+        "organizational unit",
+        end_entity(pkcs7, |cert| cert.organizational_unit.to_owned()),
+        end_entity(cose, |cert| cert.organizational_unit.to_owned()),
+    );
+    compare(
+        MismatchKind::Kind,
+        "kind",
+        pkcs7.kind().to_string(),
+        cose.kind().to_string(),
+    );
+    compare(
+        MismatchKind::Expiry,
+        "end date",
+        end_entity(pkcs7, |cert| cert.end_date.to_string()),
+        end_entity(cose, |cert| cert.end_date.to_string()),
+    );
+
+    let manifest = read_file(archive, PKCS7_MANIFEST_FILE).unwrap_or_default();
+    let listed: Vec<String> = parse_manifest(&manifest)
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| key == "Name")
+        .map(|(_, name)| name)
+        .collect();
+    // The COSE files must be covered by the PKCS#7 signature of a dual-signed XPI.
+    for file in COSE_FILES {
+        if !listed.iter().any(|name| name == file) {
+            mismatches.push(SignatureMismatch {
+                kind: MismatchKind::NotCovered,
+                message: format!("{} is not covered by the PKCS#7 signature", file),
+            });
+        }
+    }
+
+    mismatches
+}
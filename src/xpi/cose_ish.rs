@@ -1,11 +1,17 @@
+use super::SignatureAlgorithm;
 use cms::cert::x509::{der::Decode, Certificate};
-use minicbor::data::Int;
+use minicbor::data::Type;
 use minicbor::decode::Decoder;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::convert::From;
+use std::fmt;
 
 const COSE_SIGN_TAG: u64 = 98;
-const COSE_ALG: u64 = 1;
-const COSE_KID: u64 = 4;
+const COSE_ALG: i64 = 1;
+const COSE_KID: i64 = 4;
+/// See RFC 9360.
+const COSE_X5CHAIN: i64 = 33;
 
 pub enum CoseError {
     InvalidTag,
@@ -19,16 +25,43 @@ impl From<minicbor::decode::Error> for CoseError {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents a deviation from RFC 8152 found in the COSE signatures produced by Mozilla.
+pub enum CoseQuirk {
+    /// The `kid` header of the `COSE_Sign` structure is an array of (intermediate) certificates
+    /// instead of a `bstr`. See: https://github.com/franziskuskiefer/cose-rust/issues/60
+    KidArray,
+    /// The `kid` header of a `COSE_Signature` structure contains the signer certificate instead
+    /// of a key identifier. RFC 9360 defines the `x5chain` header for this purpose.
+    KidCertificate,
+}
+
+impl fmt::Display for CoseQuirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CoseQuirk::KidArray => "kid is an array of certificates",
+                CoseQuirk::KidCertificate => "kid contains a certificate",
+            }
+        )
+    }
+}
+
 pub struct CoseSign {
-    pub algorithm: String,
+    pub algorithm: Option<SignatureAlgorithm>,
     pub certificates: Vec<Certificate>,
+    pub quirks: Vec<CoseQuirk>,
 }
 
 impl CoseSign {
     pub(crate) fn parse(bytes: &[u8]) -> Result<Self, CoseError> {
         let mut decoder = Decoder::new(bytes);
 
-        if decoder.tag()?.as_u64() != COSE_SIGN_TAG {
+        // The tag can be omitted when the type is known from the context.
+        if decoder.datatype()? == Type::Tag && decoder.tag()?.as_u64() != COSE_SIGN_TAG {
             return Err(CoseError::InvalidTag);
         }
 
@@ -37,105 +70,217 @@ impl CoseSign {
         // COSE_Sign = [
         //   protected : serialized_map,
         //   unprotected : header_map
-        //   payload : nil,
+        //   payload : bstr / nil,
         //   signatures : [COSE_Signature, ...]
         // ]
         //
-        match decoder.array()? {
-            Some(4) => {}
-            _ => return Err(CoseError::MalformedInput),
-        };
-
-        // protected should contain the intermediate certificates.
-        let mut certificates = vec![];
-        let protected = decoder.bytes()?;
-        let mut dec = Decoder::new(protected);
-        // We expect a map with an array in it.
-        match dec.map()? {
-            Some(1) => {}
-            _ => return Err(CoseError::MalformedInput),
-        };
-
-        if dec.int()? == Int::from(COSE_KID) {
-            // Important: this is no RFC 8152 compliant because `kid` should be `bstr`, not an
-            // `array`. See: https://github.com/franziskuskiefer/cose-rust/issues/60
-            let size = match dec.array()? {
-                Some(size) => size,
-                None => return Err(CoseError::MalformedInput),
-            };
-
-            // Decode all the intermediate certificates.
-            for _ in 0..size {
-                let data = dec.bytes()?;
-                if let Ok(cert) = Certificate::from_der(data) {
-                    certificates.push(cert);
-                }
-            }
-        }
+        let indefinite = open_array(&mut decoder, 4)?;
 
-        // unprotected should be an empty map.
-        match decoder.map()? {
-            Some(0) => {}
-            _ => return Err(CoseError::MalformedInput),
+        // The headers of Mozilla signatures contain the intermediate certificates.
+        let mut sign = CoseSign {
+            algorithm: None,
+            certificates: vec![],
+            quirks: vec![],
         };
+        sign.parse_protected(&mut decoder)?;
+        sign.parse_headers(&mut decoder)?;
 
-        // payload should be null because this is a detached signature.
-        decoder.null()?;
+        // payload is null for a detached signature, which is the case of Mozilla signatures.
+        decoder.skip()?;
 
-        // signatures
-        let mut algorithm: String = "UNKNOWN".to_owned();
-        let size = match decoder.array()? {
-            Some(size) => size,
-            None => return Err(CoseError::MalformedInput),
-        };
-        for _ in 0..size {
+        let len = decoder.array()?;
+        let mut signers = vec![];
+        for_each(&mut decoder, len, |dec| {
             // COSE_Signature =  [
             //   protected : serialized_map,
             //   unprotected : header_map
             //   signature : bstr
             // ]
-            match decoder.array()? {
-                Some(3) => {}
-                _ => return Err(CoseError::MalformedInput),
+            let indefinite = open_array(dec, 3)?;
+            let mut signer = CoseSign {
+                algorithm: None,
+                certificates: vec![],
+                quirks: vec![],
             };
+            signer.parse_protected(dec)?;
+            signer.parse_headers(dec)?;
+            read_bytes(dec)?;
+            close_array(dec, indefinite)?;
 
-            let protected = decoder.bytes()?;
-            let mut dec = Decoder::new(protected);
-            // We expect a map with 2 entries: `alg` and `kid`.
-            match dec.map()? {
-                Some(2) => {}
-                _ => return Err(CoseError::MalformedInput),
-            };
+            signers.push(signer);
+            Ok(())
+        })?;
 
-            if dec.int()? == Int::from(COSE_ALG) {
-                let val = dec.int()?;
-                algorithm = if val == Int::from(-7) {
-                    "ES256".to_owned()
-                } else if val == Int::from(-35) {
-                    "ES384".to_owned()
-                } else if val == Int::from(-36) {
-                    "ES512".to_owned()
-                } else {
-                    algorithm
-                };
+        close_array(&mut decoder, indefinite)?;
+
+        // The algorithm of the first signer is reported, the certificates of the signers come
+        // after the ones of the `COSE_Sign` structure.
+        let algorithm = signers
+            .iter_mut()
+            .find_map(|signer| signer.algorithm.take())
+            .or(sign.algorithm.take());
+        for signer in signers {
+            sign.certificates.extend(signer.certificates);
+            for quirk in signer.quirks {
+                sign.add_quirk(quirk);
             }
+        }
+        sign.algorithm = algorithm;
+
+        Ok(sign)
+    }
+
+    /// Parses the protected headers, i.e. a header map serialized in a `bstr`.
+    fn parse_protected(&mut self, decoder: &mut Decoder) -> Result<(), CoseError> {
+        let protected = read_bytes(decoder)?;
+        // An empty `bstr` is used when there is no protected header.
+        if protected.is_empty() {
+            return Ok(());
+        }
 
-            if dec.int()? == Int::from(COSE_KID) {
-                let data = dec.bytes()?;
-                if let Ok(cert) = Certificate::from_der(data) {
-                    certificates.push(cert);
+        self.parse_headers(&mut Decoder::new(&protected))
+    }
+
+    /// Parses a header map. Labels can be integers or text strings, and the headers we do not
+    /// know about are skipped.
+    fn parse_headers(&mut self, decoder: &mut Decoder) -> Result<(), CoseError> {
+        let len = decoder.map()?;
+        for_each(decoder, len, |dec| {
+            match read_int(dec)? {
+                Some(COSE_ALG) => {
+                    // The algorithm can also be a text string, which we do not support.
+                    if let Some(id) = read_int(dec)? {
+                        self.algorithm = Some(SignatureAlgorithm::from_cose(id));
+                    }
                 }
+                Some(COSE_KID) => match dec.datatype()? {
+                    Type::Array | Type::ArrayIndef => {
+                        self.add_quirk(CoseQuirk::KidArray);
+                        read_certificates(dec, &mut self.certificates)?;
+                    }
+                    _ => {
+                        let kid = read_bytes(dec)?;
+                        if let Ok(cert) = Certificate::from_der(&kid) {
+                            self.add_quirk(CoseQuirk::KidCertificate);
+                            self.certificates.push(cert);
+                        }
+                    }
+                },
+                Some(COSE_X5CHAIN) => {
+                    // The chain starts with the end-entity certificate but we list the
+                    // intermediate certificates first.
+                    let mut chain = vec![];
+                    read_certificates(dec, &mut chain)?;
+                    self.certificates.extend(chain.into_iter().rev());
+                }
+                _ => dec.skip()?,
             }
+            Ok(())
+        })
+    }
 
-            // unprotected
-            decoder.map()?;
-            // signature
-            decoder.bytes()?;
+    fn add_quirk(&mut self, quirk: CoseQuirk) {
+        if !self.quirks.contains(&quirk) {
+            self.quirks.push(quirk);
         }
+    }
+}
 
-        Ok(CoseSign {
-            algorithm,
-            certificates,
-        })
+/// Calls `f` for each item of an array (or each entry of a map) of definite or indefinite
+/// length `len`.
+fn for_each<'b, F>(decoder: &mut Decoder<'b>, len: Option<u64>, mut f: F) -> Result<(), CoseError>
+where
+    F: FnMut(&mut Decoder<'b>) -> Result<(), CoseError>,
+{
+    match len {
+        Some(len) => {
+            for _ in 0..len {
+                f(decoder)?;
+            }
+        }
+        None => {
+            while decoder.datatype()? != Type::Break {
+                f(decoder)?;
+            }
+            decoder.skip()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the header of an array of `size` items and returns whether it has an indefinite length.
+fn open_array(decoder: &mut Decoder, size: u64) -> Result<bool, CoseError> {
+    match decoder.array()? {
+        Some(len) if len == size => Ok(false),
+        None => Ok(true),
+        _ => Err(CoseError::MalformedInput),
+    }
+}
+
+/// Reads the end of an array opened with [`open_array`].
+fn close_array(decoder: &mut Decoder, indefinite: bool) -> Result<(), CoseError> {
+    if indefinite {
+        if decoder.datatype()? != Type::Break {
+            return Err(CoseError::MalformedInput);
+        }
+        decoder.skip()?;
+    }
+
+    Ok(())
+}
+
+/// Reads an integer, or skips the current item and returns `None` when it is not an integer
+/// (e.g. a text string label).
+fn read_int(decoder: &mut Decoder) -> Result<Option<i64>, CoseError> {
+    match decoder.datatype()? {
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::Int => Ok(i64::try_from(decoder.int()?).ok()),
+        _ => {
+            decoder.skip()?;
+            Ok(None)
+        }
+    }
+}
+
+/// Reads a `bstr` of definite or indefinite length.
+fn read_bytes<'b>(decoder: &mut Decoder<'b>) -> Result<Cow<'b, [u8]>, CoseError> {
+    if decoder.datatype()? != Type::BytesIndef {
+        return Ok(Cow::Borrowed(decoder.bytes()?));
+    }
+
+    let mut bytes = vec![];
+    for chunk in decoder.bytes_iter()? {
+        bytes.extend_from_slice(chunk?);
+    }
+    Ok(Cow::Owned(bytes))
+}
+
+/// Reads a certificate or an array of certificates. Invalid certificates are ignored.
+fn read_certificates(
+    decoder: &mut Decoder,
+    certificates: &mut Vec<Certificate>,
+) -> Result<(), CoseError> {
+    let mut read_certificate = |dec: &mut Decoder| {
+        let data = read_bytes(dec)?;
+        if let Ok(cert) = Certificate::from_der(&data) {
+            certificates.push(cert);
+        }
+        Ok(())
+    };
+
+    match decoder.datatype()? {
+        Type::Array | Type::ArrayIndef => {
+            let len = decoder.array()?;
+            for_each(decoder, len, read_certificate)
+        }
+        _ => read_certificate(decoder),
     }
 }
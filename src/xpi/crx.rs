@@ -0,0 +1,289 @@
+use super::{entries::to_hex, Entry, Error, Manifest};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{fmt, io::Cursor};
+use zip::ZipArchive;
+
+const CRX_MAGIC: &[u8] = b"Cr24";
+const CRX_VERSION: u32 = 3;
+
+// Field numbers in the `CrxFileHeader` protobuf message.
+const HEADER_SHA256_WITH_RSA: u64 = 2;
+const HEADER_SHA256_WITH_ECDSA: u64 = 3;
+const HEADER_SIGNED_HEADER_DATA: u64 = 10000;
+// Field numbers in the `AsymmetricKeyProof` protobuf message.
+const PROOF_PUBLIC_KEY: u64 = 1;
+const PROOF_SIGNATURE: u64 = 2;
+// Field numbers in the `SignedData` protobuf message.
+const SIGNED_DATA_CRX_ID: u64 = 1;
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents the algorithm used by a key proof in a CRX header.
+pub enum KeyProofAlgorithm {
+    #[serde(rename = "sha256_with_rsa")]
+    Sha256WithRsa,
+    #[serde(rename = "sha256_with_ecdsa")]
+    Sha256WithEcdsa,
+}
+
+impl fmt::Display for KeyProofAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                KeyProofAlgorithm::Sha256WithRsa => "SHA256 WITH RSA",
+                KeyProofAlgorithm::Sha256WithEcdsa => "SHA256 WITH ECDSA",
+            }
+        )
+    }
+}
+
+#[derive(Serialize)]
+/// Represents a signature (and the public key to verify it) found in a CRX header.
+pub struct KeyProof {
+    pub algorithm: KeyProofAlgorithm,
+    /// The DER-encoded public key, hex-encoded.
+    pub public_key: String,
+    /// The signature, hex-encoded.
+    pub signature: String,
+    /// Whether this proof has been made with the developer key, i.e. the key from which the CRX
+    /// ID is derived. Other proofs are usually made by the store.
+    pub developer_key: bool,
+}
+
+#[derive(Default, Serialize)]
+/// Represents the information contained in the header of a CRX3 file.
+pub struct CrxHeader {
+    /// The CRX ID found in the signed header data, hex-encoded.
+    pub crx_id: Option<String>,
+    /// The extension ID derived from the CRX ID.
+    pub extension_id: Option<String>,
+    /// The key proofs found in the header.
+    pub proofs: Vec<KeyProof>,
+}
+
+#[derive(Serialize)]
+/// Represents a CRX3 file.
+///
+/// CRX files are used to package extensions for Chrome. They are made of a header containing
+/// signatures, followed by a ZIP archive.
+pub struct CRX {
+    /// Information about the CRX header.
+    pub header: CrxHeader,
+    /// Information about the `manifest.json` file found in the embedded ZIP archive.
+    pub manifest: Manifest,
+    /// The files stored in the embedded ZIP archive.
+    pub entries: Vec<Entry>,
+}
+
+impl CRX {
+    /// Whether `bytes` look like a CRX file, i.e. they start with the CRX magic number.
+    pub fn is_crx(bytes: &[u8]) -> bool {
+        bytes.starts_with(CRX_MAGIC)
+    }
+
+    /// Constructs a new `CRX` from the content of a CRX3 file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<CRX, Error> {
+        if !CRX::is_crx(bytes) {
+            return Err(Error::InvalidCrxHeader);
+        }
+
+        let version = read_u32(bytes, 4).ok_or(Error::InvalidCrxHeader)?;
+        if version != CRX_VERSION {
+            return Err(Error::InvalidCrxHeader);
+        }
+
+        let header_size = read_u32(bytes, 8).ok_or(Error::InvalidCrxHeader)? as usize;
+        let header_end = header_size
+            .checked_add(12)
+            .filter(|end| *end <= bytes.len())
+            .ok_or(Error::InvalidCrxHeader)?;
+        let header = CrxHeader::parse(&bytes[12..header_end]).ok_or(Error::InvalidCrxHeader)?;
+
+        let mut archive = ZipArchive::new(Cursor::new(&bytes[header_end..]))?;
+
+        Ok(CRX {
+            header,
+            manifest: Manifest::parse(&mut archive),
+            entries: Entry::parse_all(&mut archive),
+        })
+    }
+}
+
+impl CrxHeader {
+    fn parse(bytes: &[u8]) -> Option<CrxHeader> {
+        let mut header = CrxHeader::default();
+        let mut proofs = vec![];
+
+        for (field, value) in ProtoFields::new(bytes) {
+            match (field?, value) {
+                (HEADER_SHA256_WITH_RSA, Some(value)) => {
+                    proofs.push((KeyProofAlgorithm::Sha256WithRsa, value))
+                }
+                (HEADER_SHA256_WITH_ECDSA, Some(value)) => {
+                    proofs.push((KeyProofAlgorithm::Sha256WithEcdsa, value))
+                }
+                (HEADER_SIGNED_HEADER_DATA, Some(value)) => {
+                    for (field, value) in ProtoFields::new(value) {
+                        if let (SIGNED_DATA_CRX_ID, Some(crx_id)) = (field?, value) {
+                            header.crx_id = Some(to_hex(crx_id));
+                            header.extension_id = Some(extension_id(crx_id));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (algorithm, proof) in proofs {
+            let mut public_key: &[u8] = &[];
+            let mut signature: &[u8] = &[];
+            for (field, value) in ProtoFields::new(proof) {
+                match (field?, value) {
+                    (PROOF_PUBLIC_KEY, Some(value)) => public_key = value,
+                    (PROOF_SIGNATURE, Some(value)) => signature = value,
+                    _ => {}
+                }
+            }
+
+            // The CRX ID is the first 16 bytes of the SHA-256 hash of the developer key.
+            let key_hash = Sha256::digest(public_key);
+            let developer_key = header
+                .crx_id
+                .as_ref()
+                .is_some_and(|crx_id| *crx_id == to_hex(&key_hash[..16]));
+
+            header.proofs.push(KeyProof {
+                algorithm,
+                public_key: to_hex(public_key),
+                signature: to_hex(signature),
+                developer_key,
+            });
+        }
+
+        Some(header)
+    }
+}
+
+impl fmt::Display for CRX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\n\nCRX HEADER:\n  Extension ID: {}\n  Key Proofs:",
+            self.manifest,
+            self.header.extension_id.as_deref().unwrap_or("N/A"),
+        )?;
+        if self.header.proofs.is_empty() {
+            write!(f, "\n   └── NONE")?;
+        }
+        for proof in &self.header.proofs {
+            write!(
+                f,
+                "\n   └── {}{}",
+                proof.algorithm,
+                if proof.developer_key {
+                    " (DEVELOPER KEY)"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts a CRX ID to an extension ID, which uses the `a-p` alphabet to encode each nibble.
+fn extension_id(crx_id: &[u8]) -> String {
+    crx_id
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .map(|nibble| (b'a' + nibble) as char)
+        .collect()
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let slice = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(slice.try_into().ok()?))
+}
+
+/// A minimal iterator over the fields of a protobuf message. Each item is the field number
+/// (`None` when the message is malformed) and, for length-delimited fields, the value.
+struct ProtoFields<'a> {
+    bytes: &'a [u8],
+    failed: bool,
+}
+
+impl<'a> ProtoFields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        ProtoFields {
+            bytes,
+            failed: false,
+        }
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value: u64 = 0;
+        for (i, byte) in self.bytes.iter().enumerate().take(10) {
+            value |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                self.bytes = &self.bytes[i + 1..];
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(..len)?;
+        self.bytes = &self.bytes[len..];
+        Some(bytes)
+    }
+
+    fn field(&mut self) -> Option<(u64, Option<&'a [u8]>)> {
+        let key = self.varint()?;
+        let value = match key & 0x07 {
+            // varint
+            0 => {
+                self.varint()?;
+                None
+            }
+            // 64-bit
+            1 => {
+                self.take(8)?;
+                None
+            }
+            // length-delimited
+            2 => {
+                let len = self.varint()?.try_into().ok()?;
+                Some(self.take(len)?)
+            }
+            // 32-bit
+            5 => {
+                self.take(4)?;
+                None
+            }
+            _ => return None,
+        };
+
+        Some((key >> 3, value))
+    }
+}
+
+impl<'a> Iterator for ProtoFields<'a> {
+    type Item = (Option<u64>, Option<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.bytes.is_empty() {
+            return None;
+        }
+
+        match self.field() {
+            Some((field, value)) => Some((Some(field), value)),
+            None => {
+                self.failed = true;
+                Some((None, None))
+            }
+        }
+    }
+}
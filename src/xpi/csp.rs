@@ -0,0 +1,241 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the pages to which a content security policy applies.
+pub enum CspContext {
+    /// The extension pages, i.e. the `content_security_policy` string in Manifest V2 and the
+    /// `content_security_policy.extension_pages` property in Manifest V3.
+    ExtensionPages,
+    /// The sandboxed pages (`content_security_policy.sandbox` in Manifest V3).
+    Sandbox,
+}
+
+impl fmt::Display for CspContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CspContext::ExtensionPages => "extension_pages",
+                CspContext::Sandbox => "sandbox",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a directive of a content security policy, e.g. `script-src 'self'`.
+pub struct Directive {
+    /// The name of the directive, in lowercase.
+    pub name: String,
+    /// The sources (or values) of the directive.
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the kinds of weaknesses that can be found in a content security policy.
+pub enum CspWeaknessKind {
+    /// `'unsafe-eval'` allows `eval()` and similar constructs.
+    UnsafeEval,
+    /// `'unsafe-inline'` allows inline scripts.
+    UnsafeInline,
+    /// `'wasm-unsafe-eval'` allows the compilation of WebAssembly modules.
+    WasmUnsafeEval,
+    /// A script source that is not part of the add-on, e.g. `https://cdn.example.com`.
+    RemoteScript,
+    /// A source matching many origins, e.g. `*`, `https:` or `https://*.example.com`.
+    WildcardSource,
+    /// Neither `object-src` nor `default-src` is declared.
+    MissingObjectSrc,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a weakness found in a content security policy.
+pub struct CspWeakness {
+    pub kind: CspWeaknessKind,
+    /// The directive in which the weakness has been found, if any.
+    pub directive: Option<String>,
+    /// The source causing the weakness, if any.
+    pub source: Option<String>,
+}
+
+impl fmt::Display for CspWeakness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let directive = self.directive.as_deref().unwrap_or("N/A");
+        let source = self.source.as_deref().unwrap_or("N/A");
+
+        match self.kind {
+            CspWeaknessKind::UnsafeEval
+            | CspWeaknessKind::UnsafeInline
+            | CspWeaknessKind::WasmUnsafeEval => write!(f, "{} allows {}", directive, source),
+            CspWeaknessKind::RemoteScript => {
+                write!(f, "{} allows remote scripts from {}", directive, source)
+            }
+            CspWeaknessKind::WildcardSource => {
+                write!(f, "{} uses a wildcard source: {}", directive, source)
+            }
+            CspWeaknessKind::MissingObjectSrc => {
+                write!(f, "missing object-src (or default-src) directive")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a content security policy declared in the `content_security_policy` property of the
+/// manifest.
+pub struct ContentSecurityPolicy {
+    pub context: CspContext,
+    /// The policy, as written in the manifest.
+    pub policy: String,
+    pub directives: Vec<Directive>,
+    /// The weaknesses found in the policy that are relevant to add-on review.
+    pub weaknesses: Vec<CspWeakness>,
+}
+
+impl ContentSecurityPolicy {
+    /// Parses a policy (e.g. `script-src 'self'; object-src 'self'`) and looks for weaknesses.
+    pub fn parse(context: CspContext, policy: &str) -> ContentSecurityPolicy {
+        let mut directives: Vec<Directive> = vec![];
+        for directive in policy.split(';') {
+            let mut tokens = directive.split_ascii_whitespace();
+            let Some(name) = tokens.next() else {
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            // Browsers ignore the duplicate directives.
+            if directives.iter().any(|directive| directive.name == name) {
+                continue;
+            }
+            directives.push(Directive {
+                name,
+                sources: tokens.map(|token| token.to_owned()).collect(),
+            });
+        }
+
+        let mut csp = ContentSecurityPolicy {
+            context,
+            policy: policy.to_owned(),
+            directives,
+            weaknesses: vec![],
+        };
+        csp.weaknesses = csp.find_weaknesses();
+        csp
+    }
+
+    /// Returns the directive named `name`, if any.
+    pub fn directive(&self, name: &str) -> Option<&Directive> {
+        self.directives
+            .iter()
+            .find(|directive| directive.name == name)
+    }
+
+    /// Parses the `content_security_policy` property of a manifest, which is a string in
+    /// Manifest V2 and an object in Manifest V3.
+    pub(crate) fn from_manifest(value: Option<&Value>) -> Vec<ContentSecurityPolicy> {
+        match value {
+            Some(Value::String(policy)) => {
+                vec![ContentSecurityPolicy::parse(
+                    CspContext::ExtensionPages,
+                    policy,
+                )]
+            }
+            Some(Value::Object(policies)) => [
+                ("extension_pages", CspContext::ExtensionPages),
+                ("sandbox", CspContext::Sandbox),
+            ]
+            .into_iter()
+            .filter_map(|(key, context)| {
+                let policy = policies.get(key)?.as_str()?;
+                Some(ContentSecurityPolicy::parse(context, policy))
+            })
+            .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn find_weaknesses(&self) -> Vec<CspWeakness> {
+        let mut weaknesses = vec![];
+        let mut push = |kind, directive: &Directive, source: &str| {
+            weaknesses.push(CspWeakness {
+                kind,
+                directive: Some(directive.name.to_owned()),
+                source: Some(source.to_owned()),
+            })
+        };
+
+        // Scripts are controlled by `script-src`, which falls back to `default-src`.
+        let script_src = self
+            .directive("script-src")
+            .or(self.directive("default-src"));
+
+        for directive in &self.directives {
+            let is_script_src = script_src.is_some_and(|script_src| script_src == directive);
+
+            for source in &directive.sources {
+                let lowercase = source.to_ascii_lowercase();
+                match lowercase.as_str() {
+                    "'unsafe-eval'" if is_script_src => {
+                        push(CspWeaknessKind::UnsafeEval, directive, source)
+                    }
+                    "'unsafe-inline'" if is_script_src => {
+                        push(CspWeaknessKind::UnsafeInline, directive, source)
+                    }
+                    "'wasm-unsafe-eval'" if is_script_src => {
+                        push(CspWeaknessKind::WasmUnsafeEval, directive, source)
+                    }
+                    source_expression => {
+                        if is_wildcard(source_expression) {
+                            push(CspWeaknessKind::WildcardSource, directive, source);
+                        }
+                        if is_script_src && is_remote(source_expression) {
+                            push(CspWeaknessKind::RemoteScript, directive, source);
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.directive("object-src").is_none() && self.directive("default-src").is_none() {
+            weaknesses.push(CspWeakness {
+                kind: CspWeaknessKind::MissingObjectSrc,
+                directive: None,
+                source: None,
+            });
+        }
+
+        weaknesses
+    }
+}
+
+impl fmt::Display for ContentSecurityPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.policy)?;
+        for weakness in &self.weaknesses {
+            write!(f, "\n   └── {}", weakness)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a source expression matches many origins, e.g. `*`, `https:` or `*.example.com`.
+fn is_wildcard(source: &str) -> bool {
+    matches!(source, "*" | "http:" | "https:" | "ws:" | "wss:") || source.contains("*.")
+}
+
+/// Whether a source expression allows content that is not part of the add-on, i.e. it is neither
+/// a keyword (`'self'`, `'none'`, nonces, hashes...) nor an internal scheme.
+fn is_remote(source: &str) -> bool {
+    if source.starts_with('\'') {
+        return false;
+    }
+
+    !matches!(
+        source,
+        "blob:" | "filesystem:" | "moz-extension:" | "chrome-extension:"
+    ) && !source.starts_with("moz-extension://")
+}
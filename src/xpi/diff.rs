@@ -0,0 +1,335 @@
+use super::{source_maps::is_source_map, Signature, XPI};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a value that differs between two XPI files.
+pub struct Change {
+    /// The name of the value that has changed.
+    pub field: String,
+    /// The value in the old XPI file, if any.
+    pub old: Option<String>,
+    /// The value in the new XPI file, if any.
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the items added to or removed from a list.
+pub struct ListChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ListChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents the changes in the files stored in two XPI files.
+pub struct EntriesDiff {
+    /// The files that only exist in the new XPI file.
+    pub added: Vec<String>,
+    /// The files that only exist in the old XPI file.
+    pub removed: Vec<String>,
+    /// The files that exist in both XPI files but with a different content. The files that cannot
+    /// be read in either XPI file are considered modified, since their content is unknown.
+    pub modified: Vec<String>,
+}
+
+impl EntriesDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents the differences between two [`XPI`](`crate::XPI`) files.
+pub struct Diff {
+    /// The changes in the `manifest.json` file.
+    pub manifest: Vec<Change>,
+    /// The permissions (including host permissions) added or removed.
+    pub permissions: ListChanges,
+    /// The changes in the signatures.
+    pub signatures: Vec<Change>,
+    /// The changes in the recommendation state.
+    pub recommendation: Vec<Change>,
+    /// The changes in the files stored in the XPI files.
+    pub entries: EntriesDiff,
+}
+
+impl Diff {
+    /// Whether the two XPI files are considered identical.
+    pub fn is_empty(&self) -> bool {
+        self.manifest.is_empty()
+            && self.permissions.is_empty()
+            && self.signatures.is_empty()
+            && self.recommendation.is_empty()
+            && self.entries.is_empty()
+    }
+}
+
+impl XPI {
+    /// Compares this XPI (the "old" one) with another one (the "new" one).
+    pub fn diff(&self, other: &XPI) -> Diff {
+        let mut manifest = vec![];
+        push_change(
+            &mut manifest,
+            "manifest_version",
+            self.manifest.manifest_version.map(|v| v.to_string()),
+            other.manifest.manifest_version.map(|v| v.to_string()),
+        );
+        push_change(
+            &mut manifest,
+            "id",
+            self.manifest.id.clone(),
+            other.manifest.id.clone(),
+        );
+        push_change(
+            &mut manifest,
+            "name",
+            self.manifest.name.clone(),
+            other.manifest.name.clone(),
+        );
+        push_change(
+            &mut manifest,
+            "version",
+            self.manifest.version.clone(),
+            other.manifest.version.clone(),
+        );
+        push_change(
+            &mut manifest,
+            "admin_install_only",
+            self.manifest.admin_install_only.map(|v| v.to_string()),
+            other.manifest.admin_install_only.map(|v| v.to_string()),
+        );
+
+        let old_permissions: Vec<String> = self
+            .manifest
+            .permissions
+            .iter()
+            .chain(self.manifest.host_permissions.iter())
+            .cloned()
+            .collect();
+        let new_permissions: Vec<String> = other
+            .manifest
+            .permissions
+            .iter()
+            .chain(other.manifest.host_permissions.iter())
+            .cloned()
+            .collect();
+        let permissions = ListChanges {
+            added: new_permissions
+                .iter()
+                .filter(|p| !old_permissions.contains(p))
+                .cloned()
+                .collect(),
+            removed: old_permissions
+                .iter()
+                .filter(|p| !new_permissions.contains(p))
+                .cloned()
+                .collect(),
+        };
+
+        let mut signatures = vec![];
+        diff_signature(
+            &mut signatures,
+            "pkcs7",
+            &self.signatures.pkcs7,
+            &other.signatures.pkcs7,
+        );
+        diff_signature(
+            &mut signatures,
+            "cose",
+            &self.signatures.cose,
+            &other.signatures.cose,
+        );
+
+        let mut recommendation = vec![];
+        push_change(
+            &mut recommendation,
+            "states",
+            self.recommendation
+                .as_ref()
+                .map(|rec| format!("{:?}", rec.states)),
+            other
+                .recommendation
+                .as_ref()
+                .map(|rec| format!("{:?}", rec.states)),
+        );
+        push_change(
+            &mut recommendation,
+            "validity",
+            self.recommendation
+                .as_ref()
+                .map(|rec| format!("{} - {}", rec.validity.not_before, rec.validity.not_after)),
+            other
+                .recommendation
+                .as_ref()
+                .map(|rec| format!("{} - {}", rec.validity.not_before, rec.validity.not_after)),
+        );
+
+        let old_entries: BTreeMap<&str, &str> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.sha256.as_str()))
+            .collect();
+        let new_entries: BTreeMap<&str, &str> = other
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.sha256.as_str()))
+            .collect();
+        let mut entries = EntriesDiff::default();
+        for (name, hash) in &new_entries {
+            match old_entries.get(name) {
+                None => entries.added.push(name.to_string()),
+                // An empty hash means that the file could not be read.
+                Some(old_hash) if old_hash.is_empty() || hash.is_empty() || old_hash != hash => {
+                    entries.modified.push(name.to_string())
+                }
+                _ => {}
+            }
+        }
+        for name in old_entries.keys() {
+            if !new_entries.contains_key(name) {
+                entries.removed.push(name.to_string());
+            }
+        }
+
+        Diff {
+            manifest,
+            permissions,
+            signatures,
+            recommendation,
+            entries,
+        }
+    }
+}
+
+fn push_change(changes: &mut Vec<Change>, field: &str, old: Option<String>, new: Option<String>) {
+    if old != new {
+        changes.push(Change {
+            field: field.to_owned(),
+            old,
+            new,
+        });
+    }
+}
+
+fn diff_signature(changes: &mut Vec<Change>, name: &str, old: &Signature, new: &Signature) {
+    push_change(
+        changes,
+        &format!("{}.present", name),
+        Some(old.exists().to_string()),
+        Some(new.exists().to_string()),
+    );
+    push_change(
+        changes,
+        &format!("{}.env", name),
+        Some(old.env().to_string()),
+        Some(new.env().to_string()),
+    );
+    push_change(
+        changes,
+        &format!("{}.kind", name),
+        Some(old.kind().to_string()),
+        Some(new.kind().to_string()),
+    );
+    push_change(
+        changes,
+        &format!("{}.algorithm", name),
+        old.algorithm
+            .as_ref()
+            .map(|algorithm| algorithm.to_string()),
+        new.algorithm
+            .as_ref()
+            .map(|algorithm| algorithm.to_string()),
+    );
+
+    let count = old.certificates.len().max(new.certificates.len());
+    for index in 0..count {
+        let describe = |signature: &Signature| {
+            signature.certificates.get(index).map(|cert| {
+                format!(
+                    "CN={}, OU={}, End Date={}",
+                    cert.common_name, cert.organizational_unit, cert.end_date
+                )
+            })
+        };
+        push_change(
+            changes,
+            &format!("{}.certificates[{}]", name, index),
+            describe(old),
+            describe(new),
+        );
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            self.old.as_deref().unwrap_or("N/A"),
+            self.new.as_deref().unwrap_or("N/A")
+        )
+    }
+}
+
+fn write_changes(f: &mut fmt::Formatter<'_>, title: &str, changes: &[Change]) -> fmt::Result {
+    write!(f, "{}:", title)?;
+    if changes.is_empty() {
+        return write!(f, "\n  NONE");
+    }
+    for change in changes {
+        write!(f, "\n  {}", change)?;
+    }
+    Ok(())
+}
+
+fn write_entry(f: &mut fmt::Formatter<'_>, sign: char, name: &str) -> fmt::Result {
+    write!(f, "\n  {} {}", sign, name)?;
+    if is_source_map(name) {
+        write!(f, " (source map)")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_changes(f, "MANIFEST", &self.manifest)?;
+
+        write!(f, "\n\nPERMISSIONS:")?;
+        if self.permissions.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for permission in &self.permissions.added {
+            write!(f, "\n  + {}", permission)?;
+        }
+        for permission in &self.permissions.removed {
+            write!(f, "\n  - {}", permission)?;
+        }
+
+        write!(f, "\n\n")?;
+        write_changes(f, "RECOMMENDATION", &self.recommendation)?;
+        write!(f, "\n\n")?;
+        write_changes(f, "SIGNATURES", &self.signatures)?;
+
+        write!(f, "\n\nENTRIES:")?;
+        if self.entries.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for name in &self.entries.added {
+            write_entry(f, '+', name)?;
+        }
+        for name in &self.entries.removed {
+            write_entry(f, '-', name)?;
+        }
+        for name in &self.entries.modified {
+            write_entry(f, '~', name)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use super::source_maps::is_source_map;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{fmt::Write, io, io::Read};
+use zip::ZipArchive;
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a file stored in an [`XPI`](`crate::XPI`).
+pub struct Entry {
+    /// The path of the file in the archive.
+    pub name: String,
+    /// The uncompressed size of the file, in bytes.
+    pub size: u64,
+    /// The SHA-256 hash of the file content, hex-encoded.
+    pub sha256: String,
+    /// Whether the file is a source map (`.map` file).
+    pub is_source_map: bool,
+}
+
+impl Entry {
+    /// Returns the list of files (directories are ignored) found in the archive, in the order in
+    /// which they are stored.
+    pub(crate) fn parse_all<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> Vec<Entry> {
+        let mut entries = vec![];
+
+        for index in 0..archive.len() {
+            let Ok(mut file) = archive.by_index(index) else {
+                continue;
+            };
+            if file.is_dir() {
+                continue;
+            }
+
+            let mut hasher = Sha256::new();
+            let mut buffer = [0; 8192];
+            let mut complete = true;
+            loop {
+                match file.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => hasher.update(&buffer[..n]),
+                    Err(_) => {
+                        complete = false;
+                        break;
+                    }
+                }
+            }
+
+            entries.push(Entry {
+                name: file.name().to_owned(),
+                size: file.size(),
+                // An empty hash indicates that the file could not be read (e.g., because it is
+                // corrupted).
+                sha256: if complete {
+                    to_hex(&hasher.finalize())
+                } else {
+                    String::new()
+                },
+                is_source_map: is_source_map(file.name()),
+            });
+        }
+
+        entries
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, byte| {
+        let _ = write!(s, "{:02x}", byte);
+        s
+    })
+}
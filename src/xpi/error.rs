@@ -0,0 +1,49 @@
+use std::{error, fmt, io};
+use zip::result::ZipError;
+
+#[derive(Debug)]
+/// Represents the errors that can occur when opening an [`XPI`](`crate::XPI`) file.
+pub enum Error {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid ZIP archive (e.g., it is truncated or corrupted).
+    Zip(ZipError),
+    /// The file does not start with a valid CRX3 header.
+    InvalidCrxHeader,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Error::Io(_) => "failed to read file",
+                Error::Zip(_) => "failed to read ZIP archive",
+                Error::InvalidCrxHeader => "invalid CRX header",
+            }
+        )
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Zip(err) => Some(err),
+            Error::InvalidCrxHeader => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<ZipError> for Error {
+    fn from(err: ZipError) -> Self {
+        Error::Zip(err)
+    }
+}
@@ -0,0 +1,302 @@
+use super::{references::normalize, Error, Manifest};
+use quick_xml::{events::Event, Reader};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fmt,
+    io::{self, Cursor, Read},
+};
+use zip::ZipArchive;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the manifest properties declaring icons.
+pub enum IconSource {
+    /// The `icons` property, i.e. the icons of the add-on itself.
+    Icons,
+    Action,
+    BrowserAction,
+    PageAction,
+}
+
+impl fmt::Display for IconSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                IconSource::Icons => "icons",
+                IconSource::Action => "action.default_icon",
+                IconSource::BrowserAction => "browser_action.default_icon",
+                IconSource::PageAction => "page_action.default_icon",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the image formats supported for icons.
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ImageFormat::Png => "PNG",
+                ImageFormat::Svg => "SVG",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents an icon declared in the manifest.
+pub struct Icon {
+    pub source: IconSource,
+    /// The size declared in the manifest, in pixels. This is `None` when a single icon is declared
+    /// for all sizes, e.g. `"default_icon": "icon.svg"`.
+    pub size: Option<u32>,
+    /// The path of the image in the archive.
+    pub path: String,
+    /// The format of the image, `None` when the file is missing or not a PNG/SVG image.
+    pub format: Option<ImageFormat>,
+    /// The width of the image, in pixels, when it could be determined.
+    pub width: Option<u32>,
+    /// The height of the image, in pixels, when it could be determined.
+    pub height: Option<u32>,
+    /// The problems found while validating the image.
+    pub problems: Vec<String>,
+}
+
+impl Icon {
+    /// Returns the icons declared in the manifest, validating the images stored in the archive.
+    pub(crate) fn parse_all<R: io::Read + io::Seek>(
+        manifest: &Manifest,
+        archive: &mut ZipArchive<R>,
+    ) -> Vec<Icon> {
+        let data = &manifest.data;
+        let mut declared: Vec<(IconSource, Option<u32>, &str)> = vec![];
+
+        for (source, value) in [
+            (IconSource::Icons, data.get("icons")),
+            (IconSource::Action, default_icon(data, "action")),
+            (
+                IconSource::BrowserAction,
+                default_icon(data, "browser_action"),
+            ),
+            (IconSource::PageAction, default_icon(data, "page_action")),
+        ] {
+            match value {
+                Some(Value::String(path)) => declared.push((source, None, path)),
+                Some(Value::Object(icons)) => {
+                    let mut icons: Vec<(IconSource, Option<u32>, &str)> = icons
+                        .iter()
+                        .filter_map(|(size, path)| {
+                            Some((source, Some(size.parse().ok()?), path.as_str()?))
+                        })
+                        .collect();
+                    icons.sort_by_key(|(_, size, _)| *size);
+                    declared.extend(icons);
+                }
+                _ => {}
+            }
+        }
+
+        declared
+            .into_iter()
+            .filter_map(|(source, size, path)| {
+                let path = normalize(path)?;
+                let mut icon = Icon {
+                    source,
+                    size,
+                    path,
+                    format: None,
+                    width: None,
+                    height: None,
+                    problems: vec![],
+                };
+                icon.validate(archive);
+
+                Some(icon)
+            })
+            .collect()
+    }
+
+    /// Whether the image exists and matches the size declared in the manifest.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Reads the content of the image from an archive.
+    pub fn read<R: io::Read + io::Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+    ) -> Result<Vec<u8>, Error> {
+        let mut file = archive.by_name(&self.path)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+
+        Ok(content)
+    }
+
+    /// Reads the content of the image from the content of an XPI file.
+    pub fn read_from_bytes(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+        self.read(&mut archive)
+    }
+
+    fn validate<R: io::Read + io::Seek>(&mut self, archive: &mut ZipArchive<R>) {
+        let Ok(content) = self.read(archive) else {
+            self.problems.push("file not found".to_owned());
+            return;
+        };
+
+        if let Some((width, height)) = png_dimensions(&content) {
+            self.format = Some(ImageFormat::Png);
+            self.width = Some(width);
+            self.height = Some(height);
+            if width != height {
+                self.problems
+                    .push(format!("image is not square ({}x{})", width, height));
+            }
+            if let Some(size) = self.size.filter(|size| *size != width || *size != height) {
+                self.problems.push(format!(
+                    "expected {}x{} pixels, found {}x{}",
+                    size, size, width, height
+                ));
+            }
+        } else if let Some((width, height)) = svg_dimensions(&content) {
+            // SVG images are scaled to the declared size, there is nothing more to check.
+            self.format = Some(ImageFormat::Svg);
+            self.width = width;
+            self.height = height;
+        } else {
+            self.problems.push("not a PNG or SVG image".to_owned());
+        }
+    }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some(size) = self.size {
+            write!(f, ".{}", size)?;
+        }
+        write!(f, ": {}", self.path)?;
+        match (self.format, self.width, self.height) {
+            (Some(format), Some(width), Some(height)) => {
+                write!(f, " ({}, {}x{})", format, width, height)?
+            }
+            (Some(format), _, _) => write!(f, " ({})", format)?,
+            _ => {}
+        }
+        for problem in &self.problems {
+            write!(f, "\n   └── {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the most appropriate icon for the given size. The valid icons of the add-on are
+/// preferred over the icons of its actions, then an icon of the exact size is preferred over the
+/// smallest larger icon, and the largest icon is returned when all icons are smaller.
+pub(crate) fn find_icon(icons: &[Icon], size: u32) -> Option<&Icon> {
+    let valid: Vec<&Icon> = icons.iter().filter(|icon| icon.is_valid()).collect();
+    let candidates: Vec<&Icon> = if valid.iter().any(|icon| icon.source == IconSource::Icons) {
+        valid
+            .into_iter()
+            .filter(|icon| icon.source == IconSource::Icons)
+            .collect()
+    } else {
+        valid
+    };
+
+    // Icons declared for all sizes fit any size but an icon declared with the exact size wins.
+    let larger = candidates
+        .iter()
+        .filter(|icon| icon.size.is_none_or(|s| s >= size))
+        .min_by_key(|icon| icon.size.unwrap_or(u32::MAX));
+    let largest = candidates.iter().max_by_key(|icon| icon.size);
+
+    larger.or(largest).copied()
+}
+
+fn default_icon<'a>(data: &'a Value, action: &str) -> Option<&'a Value> {
+    data.get(action)
+        .and_then(|action| action.get("default_icon"))
+}
+
+/// Returns the dimensions of a PNG image, read from its `IHDR` chunk.
+fn png_dimensions(content: &[u8]) -> Option<(u32, u32)> {
+    if !content.starts_with(PNG_SIGNATURE) || content.get(12..16) != Some(b"IHDR") {
+        return None;
+    }
+
+    let width = u32::from_be_bytes(content.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(content.get(20..24)?.try_into().ok()?);
+
+    Some((width, height))
+}
+
+/// Returns the dimensions of an SVG image, read from the `width` and `height` attributes of the
+/// root element or, as a fallback, from its `viewBox` attribute. `None` is returned when the
+/// content is not an SVG image.
+fn svg_dimensions(content: &[u8]) -> Option<(Option<u32>, Option<u32>)> {
+    let content = std::str::from_utf8(content).ok()?;
+    let mut reader = Reader::from_str(content);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                if element.local_name().as_ref() != b"svg" {
+                    return None;
+                }
+
+                let (mut width, mut height, mut view_box) = (None, None, None);
+                for attribute in element.attributes().flatten() {
+                    let Ok(value) = attribute.unescape_value() else {
+                        continue;
+                    };
+                    match attribute.key.local_name().as_ref() {
+                        b"width" => width = parse_length(&value),
+                        b"height" => height = parse_length(&value),
+                        b"viewBox" => {
+                            let values: Vec<&str> = value
+                                .split(|c: char| c.is_whitespace() || c == ',')
+                                .filter(|value| !value.is_empty())
+                                .collect();
+                            if let [_, _, w, h] = values[..] {
+                                view_box = parse_length(w).zip(parse_length(h));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                return Some((
+                    width.or(view_box.map(|(w, _)| w)),
+                    height.or(view_box.map(|(_, h)| h)),
+                ));
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            // XML declaration, comments, doctype, etc.
+            _ => {}
+        }
+    }
+}
+
+/// Parses an SVG length in pixels, e.g. `64`, `64px` or `63.5`.
+fn parse_length(value: &str) -> Option<u32> {
+    let value: f64 = value.trim().trim_end_matches("px").parse().ok()?;
+
+    (value >= 0.0).then(|| value.round() as u32)
+}
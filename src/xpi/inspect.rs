@@ -0,0 +1,432 @@
+use super::{
+    entries::to_hex,
+    signatures::{oid_name, COSE_SIGNATURE_FILE, PKCS7_SIGNATURE_FILE},
+    verdict::read_file,
+    Error,
+};
+use cms::cert::x509::der::{asn1::ObjectIdentifier, Decode, Header, Reader, SliceReader, Tag};
+use minicbor::{data::Type, decode, Decoder};
+use serde::Serialize;
+use std::{
+    fmt,
+    fmt::Write,
+    io::{self, Cursor},
+};
+use zip::ZipArchive;
+
+/// The number of spaces used to indent the nested CBOR items.
+const CBOR_INDENT: usize = 2;
+/// The maximum nesting of the CBOR items and ASN.1 elements, which bounds the recursion when
+/// decoding crafted files.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents an element of an ASN.1 structure (DER-encoded).
+pub struct Asn1Node {
+    /// The position of the element (i.e. of its identifier octet) in the file.
+    pub offset: u32,
+    pub tag: String,
+    /// The length of the content of the element, in bytes.
+    pub length: u32,
+    /// The decoded value of a primitive element, e.g. an OID with its name or a hex-encoded
+    /// string of bytes.
+    pub value: Option<String>,
+    /// The elements of a constructed element, or the elements encapsulated in an `OCTET STRING`
+    /// or a `BIT STRING`.
+    pub children: Vec<Asn1Node>,
+}
+
+impl Asn1Node {
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(
+            f,
+            "\n  {:indent$}└── {} (offset {}, length {})",
+            "",
+            self.tag,
+            self.offset,
+            self.length,
+            indent = depth * 5
+        )?;
+        if let Some(value) = &self.value {
+            write!(f, ": {}", value)?;
+        }
+        for child in &self.children {
+            child.fmt_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+/// Represents the raw structures of the signature files of an XPI file, which is useful to
+/// debug signatures that cannot be parsed.
+pub struct Inspection {
+    /// The content of `META-INF/cose.sig` in CBOR diagnostic notation, if any.
+    pub cose: Option<String>,
+    /// The error that stopped the decoding of `META-INF/cose.sig`, if any.
+    pub cose_error: Option<String>,
+    /// The ASN.1 structure of `META-INF/mozilla.rsa`, if any.
+    pub pkcs7: Option<Vec<Asn1Node>>,
+    /// The error that stopped the decoding of `META-INF/mozilla.rsa`, if any.
+    pub pkcs7_error: Option<String>,
+}
+
+impl Inspection {
+    fn new<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> Inspection {
+        let mut inspection = Inspection {
+            cose: None,
+            cose_error: None,
+            pkcs7: None,
+            pkcs7_error: None,
+        };
+
+        if let Some(bytes) = read_file(archive, COSE_SIGNATURE_FILE) {
+            let mut diagnostic = String::new();
+            inspection.cose_error = write_cbor_sequence(&bytes, &mut diagnostic)
+                .err()
+                .map(|err| err.to_string());
+            inspection.cose = Some(diagnostic);
+        }
+
+        if let Some(bytes) = read_file(archive, PKCS7_SIGNATURE_FILE) {
+            let mut nodes = vec![];
+            inspection.pkcs7_error = parse_asn1(&bytes, 0, 0, &mut nodes).err();
+            inspection.pkcs7 = Some(nodes);
+        }
+
+        inspection
+    }
+
+    /// Inspects the signature files stored in the content of an XPI file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Inspection, Error> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+        Ok(Inspection::new(&mut archive))
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "COSE ({}):", COSE_SIGNATURE_FILE)?;
+        match &self.cose {
+            Some(diagnostic) => {
+                for line in diagnostic.lines() {
+                    write!(f, "\n  {}", line)?;
+                }
+            }
+            None => write!(f, "\n  NONE")?,
+        }
+        if let Some(err) = &self.cose_error {
+            write!(f, "\n  ERROR: {}", err)?;
+        }
+
+        write!(f, "\n\nPKCS7 ({}):", PKCS7_SIGNATURE_FILE)?;
+        match &self.pkcs7 {
+            Some(nodes) => {
+                for node in nodes {
+                    node.fmt_tree(f, 0)?;
+                }
+            }
+            None => write!(f, "\n  NONE")?,
+        }
+        if let Some(err) = &self.pkcs7_error {
+            write!(f, "\n  ERROR: {}", err)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes a sequence of CBOR items in diagnostic notation (RFC 8949), one item per line. Byte
+/// strings that contain a CBOR map or array (e.g. COSE protected headers) are written as embedded
+/// CBOR (`<< ... >>`). In case of error, the items are written up to the error.
+fn write_cbor_sequence(bytes: &[u8], out: &mut String) -> Result<(), decode::Error> {
+    let mut decoder = Decoder::new(bytes);
+    while decoder.position() < bytes.len() {
+        if decoder.position() > 0 {
+            out.push('\n');
+        }
+        write_cbor(&mut decoder, 0, 0, out)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a CBOR item indented at `depth`. `nesting` is the number of items (arrays, maps, tags
+/// and embedded CBOR) that contain it.
+fn write_cbor(
+    decoder: &mut Decoder,
+    depth: usize,
+    nesting: usize,
+    out: &mut String,
+) -> Result<(), decode::Error> {
+    if nesting > MAX_DEPTH {
+        return Err(
+            decode::Error::message(format!("more than {} nested items", MAX_DEPTH))
+                .at(decoder.position()),
+        );
+    }
+
+    let item = match decoder.datatype()? {
+        Type::Bool => decoder.bool()?.to_string(),
+        Type::Null => {
+            decoder.null()?;
+            "null".to_owned()
+        }
+        Type::Undefined => {
+            decoder.undefined()?;
+            "undefined".to_owned()
+        }
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::Int => decoder.int()?.to_string(),
+        Type::F16 => format!("{:?}", decoder.f16()?),
+        Type::F32 => format!("{:?}", decoder.f32()?),
+        Type::F64 => format!("{:?}", decoder.f64()?),
+        Type::Simple => format!("simple({})", decoder.simple()?),
+        Type::Bytes => return write_cbor_bytes(decoder.bytes()?, depth, nesting, out),
+        Type::BytesIndef => {
+            let mut chunks = vec![];
+            for chunk in decoder.bytes_iter()? {
+                chunks.push(format!("h'{}'", to_hex(chunk?)));
+            }
+            format!("(_ {})", chunks.join(", "))
+        }
+        Type::String => format!("{:?}", decoder.str()?),
+        Type::StringIndef => {
+            let mut chunks = vec![];
+            for chunk in decoder.str_iter()? {
+                chunks.push(format!("{:?}", chunk?));
+            }
+            format!("(_ {})", chunks.join(", "))
+        }
+        Type::Array | Type::ArrayIndef => {
+            let len = decoder.array()?;
+            out.push_str(if len.is_none() { "[_" } else { "[" });
+            write_cbor_items(decoder, len, depth, nesting, false, out)?;
+            "]".to_owned()
+        }
+        Type::Map | Type::MapIndef => {
+            let len = decoder.map()?;
+            out.push_str(if len.is_none() { "{_" } else { "{" });
+            write_cbor_items(decoder, len, depth, nesting, true, out)?;
+            "}".to_owned()
+        }
+        Type::Tag => {
+            let _ = write!(out, "{}(", decoder.tag()?.as_u64());
+            write_cbor(decoder, depth, nesting + 1, out)?;
+            ")".to_owned()
+        }
+        other => {
+            return Err(decode::Error::type_mismatch(other)
+                .at(decoder.position())
+                .with_message("unexpected item"))
+        }
+    };
+    out.push_str(&item);
+
+    Ok(())
+}
+
+/// Writes the items of an array (or the entries of a map) of definite or indefinite length `len`,
+/// one per line.
+fn write_cbor_items(
+    decoder: &mut Decoder,
+    len: Option<u64>,
+    depth: usize,
+    nesting: usize,
+    is_map: bool,
+    out: &mut String,
+) -> Result<(), decode::Error> {
+    let indent = " ".repeat((depth + 1) * CBOR_INDENT);
+    let mut count = 0;
+
+    loop {
+        match len {
+            Some(len) if count == len => break,
+            None if decoder.datatype()? == Type::Break => {
+                decoder.skip()?;
+                break;
+            }
+            _ => {}
+        }
+
+        out.push_str(if count == 0 { "\n" } else { ",\n" });
+        out.push_str(&indent);
+        write_cbor(decoder, depth + 1, nesting + 1, out)?;
+        if is_map {
+            out.push_str(": ");
+            write_cbor(decoder, depth + 1, nesting + 1, out)?;
+        }
+        count += 1;
+    }
+
+    if count > 0 {
+        out.push('\n');
+        out.push_str(&" ".repeat(depth * CBOR_INDENT));
+    }
+
+    Ok(())
+}
+
+fn write_cbor_bytes(
+    bytes: &[u8],
+    depth: usize,
+    nesting: usize,
+    out: &mut String,
+) -> Result<(), decode::Error> {
+    if is_embedded_cbor(bytes) {
+        out.push_str("<<");
+        write_cbor(&mut Decoder::new(bytes), depth, nesting + 1, out)?;
+        out.push_str(">>");
+    } else {
+        let _ = write!(out, "h'{}'", to_hex(bytes));
+    }
+
+    Ok(())
+}
+
+/// Whether a byte string contains exactly one CBOR map or array.
+fn is_embedded_cbor(bytes: &[u8]) -> bool {
+    let mut decoder = Decoder::new(bytes);
+    matches!(
+        decoder.datatype(),
+        Ok(Type::Map | Type::MapIndef | Type::Array | Type::ArrayIndef)
+    ) && decoder.skip().is_ok()
+        && decoder.position() == bytes.len()
+}
+
+/// Parses the DER-encoded elements of `bytes`, located at `offset` in the file and nested in
+/// `depth` elements, and adds them to `nodes`. In case of error, the elements are added up to the
+/// error.
+fn parse_asn1(
+    bytes: &[u8],
+    offset: u32,
+    depth: usize,
+    nodes: &mut Vec<Asn1Node>,
+) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "more than {} nested elements at offset {}",
+            MAX_DEPTH, offset
+        ));
+    }
+
+    let mut reader = SliceReader::new(bytes).map_err(|err| err.kind().to_string())?;
+
+    while !reader.is_finished() {
+        let position = offset + u32::from(reader.position());
+        let header = Header::decode(&mut reader)
+            .map_err(|err| format!("invalid element at offset {}: {}", position, err.kind()))?;
+        // The content of a truncated element is read up to the end of the input.
+        let truncated = header.length > reader.remaining_len();
+        let length = header.length.min(reader.remaining_len());
+        let content_offset = offset + u32::from(reader.position());
+        let content = reader
+            .read_slice(length)
+            .map_err(|err| err.kind().to_string())?;
+
+        let mut node = Asn1Node {
+            offset: position,
+            tag: header.tag.to_string(),
+            length: u32::from(header.length),
+            value: None,
+            children: vec![],
+        };
+        let mut result = Ok(());
+
+        if header.tag.is_constructed() {
+            result = parse_asn1(content, content_offset, depth + 1, &mut node.children);
+        } else if matches!(header.tag, Tag::OctetString | Tag::BitString) {
+            // Some strings encapsulate DER-encoded structures, e.g. the extensions of the
+            // certificates.
+            let skip = usize::from(header.tag == Tag::BitString);
+            let encapsulated = content.get(skip..).unwrap_or_default();
+            if !is_encapsulated_der(encapsulated)
+                || parse_asn1(
+                    encapsulated,
+                    content_offset + skip as u32,
+                    depth + 1,
+                    &mut node.children,
+                )
+                .is_err()
+            {
+                node.children.clear();
+                node.value = Some(to_hex(content));
+            }
+        } else {
+            node.value = asn1_value(header.tag, content);
+        }
+        nodes.push(node);
+
+        result?;
+        if truncated {
+            return Err(format!(
+                "truncated element at offset {}: {} of {} bytes",
+                position, length, header.length
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a string of bytes starts with a `SEQUENCE` or a `SET` covering all the bytes.
+fn is_encapsulated_der(bytes: &[u8]) -> bool {
+    let Ok(mut reader) = SliceReader::new(bytes) else {
+        return false;
+    };
+    matches!(reader.peek_tag(), Ok(Tag::Sequence | Tag::Set))
+        && reader.tlv_bytes().is_ok()
+        && reader.is_finished()
+}
+
+/// Returns the value of a primitive element in a readable form.
+fn asn1_value(tag: Tag, content: &[u8]) -> Option<String> {
+    match tag {
+        Tag::Null => None,
+        Tag::Boolean => Some((content != [0]).to_string()),
+        Tag::Integer if content.len() <= 8 => Some(
+            content
+                .iter()
+                .fold(
+                    // Negative integers are sign-extended.
+                    if content.first().is_some_and(|byte| byte & 0x80 != 0) {
+                        -1
+                    } else {
+                        0
+                    },
+                    |acc: i64, byte| (acc << 8) | i64::from(*byte),
+                )
+                .to_string(),
+        ),
+        Tag::ObjectIdentifier => Some(match ObjectIdentifier::from_bytes(content) {
+            Ok(oid) => format!("{} ({})", oid, oid_name(&oid)),
+            Err(_) => to_hex(content),
+        }),
+        Tag::Utf8String
+        | Tag::PrintableString
+        | Tag::TeletexString
+        | Tag::Ia5String
+        | Tag::VisibleString
+        | Tag::NumericString
+        | Tag::UtcTime
+        | Tag::GeneralizedTime => Some(format!("{:?}", String::from_utf8_lossy(content))),
+        Tag::BmpString => Some(format!(
+            "{:?}",
+            String::from_utf16_lossy(
+                &content
+                    .chunks(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+                    .collect::<Vec<_>>()
+            )
+        )),
+        _ => Some(to_hex(content)),
+    }
+}
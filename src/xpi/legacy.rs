@@ -0,0 +1,259 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::{fmt, io, io::Read};
+use zip::ZipArchive;
+
+// Values of the `em:type` property, see:
+// https://web.archive.org/web/2017/https://developer.mozilla.org/en-US/Add-ons/Install_Manifests#type
+const TYPE_THEME: u64 = 4;
+const TYPE_LANGUAGE_PACK: u64 = 8;
+const TYPE_DICTIONARY: u64 = 64;
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents a `targetApplication` entry of an `install.rdf` file.
+pub struct TargetApplication {
+    /// The ID of the application (e.g., `{ec8030f7-c20a-464f-9b0e-13a3a9e97384}` for Firefox).
+    pub id: Option<String>,
+    pub min_version: Option<String>,
+    pub max_version: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+/// Represents the information contained in the files used by legacy (pre-WebExtension) add-ons,
+/// i.e. `install.rdf`, `bootstrap.js` and `chrome.manifest`.
+pub struct LegacyManifest {
+    /// Whether the `install.rdf` file exists in the XPI.
+    pub has_install_rdf: bool,
+    /// Whether the `chrome.manifest` file exists in the XPI.
+    pub has_chrome_manifest: bool,
+    /// Whether the `bootstrap.js` file exists in the XPI.
+    pub has_bootstrap_js: bool,
+    /// The add-on ID (`em:id`), if any.
+    pub id: Option<String>,
+    /// The add-on version (`em:version`), if any.
+    pub version: Option<String>,
+    /// The add-on name (`em:name`), if any.
+    pub name: Option<String>,
+    /// The add-on type (`em:type`), if any.
+    pub addon_type: Option<u64>,
+    /// Whether the add-on is bootstrapped (`em:bootstrap`), i.e. it can be installed without a
+    /// restart.
+    pub bootstrap: bool,
+    /// The applications supported by the add-on (`em:targetApplication`).
+    pub target_applications: Vec<TargetApplication>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the format of an add-on.
+pub enum AddonFormat {
+    /// A WebExtension, i.e. an add-on with a `manifest.json` file.
+    WebExtension,
+    /// A legacy bootstrapped (restartless) add-on.
+    Bootstrapped,
+    /// A legacy XUL/XPCOM add-on.
+    LegacyXul,
+    /// A legacy (complete) theme.
+    Theme,
+    /// A legacy language pack.
+    LanguagePack,
+    /// A legacy dictionary.
+    Dictionary,
+    /// None of the above, e.g. a ZIP file that is not an add-on.
+    Unknown,
+}
+
+impl fmt::Display for AddonFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AddonFormat::WebExtension => "WEBEXTENSION",
+                AddonFormat::Bootstrapped => "LEGACY BOOTSTRAPPED",
+                AddonFormat::LegacyXul => "LEGACY XUL",
+                AddonFormat::Theme => "LEGACY THEME",
+                AddonFormat::LanguagePack => "LEGACY LANGUAGE PACK",
+                AddonFormat::Dictionary => "LEGACY DICTIONARY",
+                AddonFormat::Unknown => "UNKNOWN",
+            }
+        )
+    }
+}
+
+impl LegacyManifest {
+    /// Returns a `LegacyManifest` when the XPI contains an `install.rdf` or a `chrome.manifest`
+    /// file, and `None` otherwise. A `bootstrap.js` file alone is not enough, since it is a common
+    /// name for a script.
+    pub(crate) fn parse<R: io::Read + io::Seek>(
+        archive: &mut ZipArchive<R>,
+    ) -> Option<LegacyManifest> {
+        let has_chrome_manifest = archive.by_name("chrome.manifest").is_ok();
+
+        let mut manifest = match archive.by_name("install.rdf") {
+            Ok(mut file) => {
+                let mut content = String::new();
+                if file.read_to_string(&mut content).is_ok() {
+                    LegacyManifest::parse_install_rdf(&content)
+                } else {
+                    LegacyManifest::default()
+                }
+            }
+            Err(_) if has_chrome_manifest => LegacyManifest::default(),
+            Err(_) => return None,
+        };
+
+        manifest.has_chrome_manifest = has_chrome_manifest;
+        manifest.has_bootstrap_js = archive.by_name("bootstrap.js").is_ok();
+        Some(manifest)
+    }
+
+    fn parse_install_rdf(content: &str) -> LegacyManifest {
+        let mut manifest = LegacyManifest {
+            has_install_rdf: true,
+            ..LegacyManifest::default()
+        };
+
+        let mut reader = Reader::from_str(content);
+        reader.trim_text(true);
+
+        // The local names of the currently open elements, e.g. `["RDF", "Description", "id"]`.
+        let mut stack: Vec<String> = vec![];
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(element)) => {
+                    stack.push(local_name(&element));
+                    manifest.start_element(&stack, &element);
+                }
+                Ok(Event::Empty(element)) => {
+                    stack.push(local_name(&element));
+                    manifest.start_element(&stack, &element);
+                    stack.pop();
+                }
+                Ok(Event::Text(text)) => {
+                    if let (Some(name), Ok(value)) = (stack.last(), text.unescape()) {
+                        let path: Vec<&str> = stack.iter().map(|s| s.as_str()).collect();
+                        manifest.set_property(&path[..path.len() - 1], name, &value);
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    stack.pop();
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+
+        manifest
+    }
+
+    /// Handles a new element. This also reads the properties set as attributes of `Description`
+    /// elements, e.g. `<Description em:id="..." />`.
+    fn start_element(&mut self, stack: &[String], element: &BytesStart) {
+        let path: Vec<&str> = stack.iter().map(|s| s.as_str()).collect();
+        if path.last() != Some(&"Description") {
+            return;
+        }
+
+        // Each `Description` element in a `targetApplication` describes a new application.
+        if path.ends_with(&["RDF", "Description", "targetApplication", "Description"]) {
+            self.target_applications.push(TargetApplication::default());
+        }
+
+        for attribute in element.attributes().flatten() {
+            let name = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string();
+            if let Ok(value) = attribute.unescape_value() {
+                self.set_property(&path, &name, &value);
+            }
+        }
+    }
+
+    /// Sets a property found in the element located at `path`.
+    fn set_property(&mut self, path: &[&str], name: &str, value: &str) {
+        let value = value.trim().to_owned();
+
+        match path {
+            // Properties of the add-on itself.
+            [.., "RDF", "Description"] => match name {
+                "id" => self.id = Some(value),
+                "version" => self.version = Some(value),
+                "name" => self.name = Some(value),
+                "type" => self.addon_type = value.parse().ok(),
+                "bootstrap" => self.bootstrap = value == "true",
+                _ => {}
+            },
+            // Properties of a target application.
+            [.., "RDF", "Description", "targetApplication", "Description"] => {
+                if let Some(app) = self.target_applications.last_mut() {
+                    match name {
+                        "id" => app.id = Some(value),
+                        "minVersion" => app.min_version = Some(value),
+                        "maxVersion" => app.max_version = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the format of the legacy add-on. An add-on is considered bootstrapped when it sets
+    /// `em:bootstrap` or ships the `bootstrap.js` file that Firefox loads for such add-ons.
+    pub fn format(&self) -> AddonFormat {
+        match self.addon_type {
+            Some(TYPE_THEME) => AddonFormat::Theme,
+            Some(TYPE_LANGUAGE_PACK) => AddonFormat::LanguagePack,
+            Some(TYPE_DICTIONARY) => AddonFormat::Dictionary,
+            _ if self.bootstrap || self.has_bootstrap_js => AddonFormat::Bootstrapped,
+            _ => AddonFormat::LegacyXul,
+        }
+    }
+}
+
+impl fmt::Display for LegacyManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LEGACY MANIFEST:\n  \
+            install.rdf    : {}\n  \
+            chrome.manifest: {}\n  \
+            bootstrap.js   : {}\n  \
+            ID             : {}\n  \
+            Version        : {}\n  \
+            Type           : {}\n  \
+            Bootstrap      : {}\n  \
+            Target Applications:",
+            if self.has_install_rdf { "Yes" } else { "No" },
+            if self.has_chrome_manifest {
+                "Yes"
+            } else {
+                "No"
+            },
+            if self.has_bootstrap_js { "Yes" } else { "No" },
+            self.id.as_deref().unwrap_or("N/A"),
+            self.version.as_deref().unwrap_or("N/A"),
+            self.addon_type
+                .map(|t| t.to_string())
+                .unwrap_or("N/A".to_owned()),
+            if self.bootstrap { "Yes" } else { "No" },
+        )?;
+        if self.target_applications.is_empty() {
+            write!(f, "\n   └── NONE")?;
+        }
+        for app in &self.target_applications {
+            write!(
+                f,
+                "\n   └── {} ({} - {})",
+                app.id.as_deref().unwrap_or("N/A"),
+                app.min_version.as_deref().unwrap_or("N/A"),
+                app.max_version.as_deref().unwrap_or("N/A"),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).to_string()
+}
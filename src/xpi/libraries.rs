@@ -0,0 +1,166 @@
+use super::entries::to_hex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{cmp::Ordering, collections::HashMap, fmt, sync::OnceLock};
+
+/// The placeholder for the version in the patterns of the database.
+const VERSION_PLACEHOLDER: &str = "{version}";
+
+#[derive(Deserialize)]
+/// Represents a library in the bundled database (`libraries.json`).
+struct LibraryDefinition {
+    name: String,
+    /// Strings (usually found in banner comments) containing a `{version}` placeholder.
+    patterns: Vec<String>,
+    /// Strings that must also be found in the file for the patterns to be considered, when the
+    /// patterns are too generic.
+    #[serde(default)]
+    requires: Vec<String>,
+    /// The first version without known vulnerabilities, if any.
+    safe_version: Option<String>,
+    /// The SHA-256 hashes of the released files, mapped to their versions.
+    #[serde(default)]
+    hashes: HashMap<String, String>,
+}
+
+fn database() -> &'static [LibraryDefinition] {
+    static DATABASE: OnceLock<Vec<LibraryDefinition>> = OnceLock::new();
+
+    DATABASE.get_or_init(|| {
+        serde_json::from_str(include_str!("libraries.json")).expect("invalid libraries database")
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents how a library has been identified.
+pub enum DetectionMethod {
+    /// The file is a known release of the library.
+    Hash,
+    /// The file contains the banner (or another marker) of the library. The library might be
+    /// bundled with other code.
+    Banner,
+}
+
+impl fmt::Display for DetectionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DetectionMethod::Hash => "HASH",
+                DetectionMethod::Banner => "BANNER",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a well-known library found in a file.
+pub struct DetectedLibrary {
+    pub name: String,
+    pub version: String,
+    /// The path of the file in the archive.
+    pub path: String,
+    pub method: DetectionMethod,
+    /// Whether the version is older than the first version without known vulnerabilities.
+    pub outdated: bool,
+}
+
+impl fmt::Display for DetectedLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} ({}){}",
+            self.path,
+            self.name,
+            self.version,
+            self.method,
+            if self.outdated { " [OUTDATED]" } else { "" }
+        )
+    }
+}
+
+/// Identifies the well-known libraries in the content of a file, `path` is only used to build the
+/// results. A file can contain several libraries when it has been bundled.
+pub fn detect_libraries(path: &str, content: &[u8]) -> Vec<DetectedLibrary> {
+    let hash = to_hex(&Sha256::digest(content));
+    let text = String::from_utf8_lossy(content);
+
+    database()
+        .iter()
+        .filter_map(|library| {
+            let (version, method) = match library.hashes.get(&hash) {
+                Some(version) => (version.to_owned(), DetectionMethod::Hash),
+                None => (find_version(library, &text)?, DetectionMethod::Banner),
+            };
+
+            Some(DetectedLibrary {
+                name: library.name.to_owned(),
+                outdated: library
+                    .safe_version
+                    .as_ref()
+                    .is_some_and(|safe| compare_versions(&version, safe) == Ordering::Less),
+                version,
+                path: path.to_owned(),
+                method,
+            })
+        })
+        .collect()
+}
+
+/// Returns the version of the library when one of its patterns is found in `text`.
+fn find_version(library: &LibraryDefinition, text: &str) -> Option<String> {
+    if !library.requires.is_empty() && !library.requires.iter().any(|marker| text.contains(marker))
+    {
+        return None;
+    }
+
+    // When several patterns match, the first occurrence in the file wins.
+    library
+        .patterns
+        .iter()
+        .filter_map(|pattern| {
+            let (prefix, suffix) = pattern.split_once(VERSION_PLACEHOLDER)?;
+            text.match_indices(prefix).find_map(|(offset, _)| {
+                let rest = &text[offset + prefix.len()..];
+                let length = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+                    .unwrap_or(rest.len());
+                let version = rest[..length].trim_end_matches('.');
+                if !version.starts_with(|c: char| c.is_ascii_digit())
+                    || !rest[version.len()..].starts_with(suffix)
+                {
+                    return None;
+                }
+
+                Some((offset, version.to_owned()))
+            })
+        })
+        .min_by_key(|(offset, _)| *offset)
+        .map(|(_, version)| version)
+}
+
+/// Compares two versions number by number, e.g. `3.10.0` is greater than `3.9.1`. Pre-release
+/// suffixes are ignored.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let numbers = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (numbers(a), numbers(b));
+
+    for index in 0..a.len().max(b.len()) {
+        let ordering = a.get(index).unwrap_or(&0).cmp(b.get(index).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
@@ -1,12 +1,41 @@
+use super::ContentSecurityPolicy;
 use json_comments::StripComments;
 use serde::Serialize;
 use std::{fmt, io};
 use zip::ZipArchive;
 
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents an entry of the `content_scripts` property of the manifest file.
+pub struct ContentScript {
+    /// The match patterns of the pages into which the scripts are injected.
+    pub matches: Vec<String>,
+    /// The match patterns of the pages excluded from `matches`.
+    pub exclude_matches: Vec<String>,
+    /// The JavaScript files to inject.
+    pub js: Vec<String>,
+    /// The CSS files to inject.
+    pub css: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents an entry of the `web_accessible_resources` property of the manifest file. In
+/// Manifest V2, this property is a list of paths, which is represented by a single entry without
+/// match patterns.
+pub struct WebAccessibleResource {
+    /// The paths of the resources, which may contain wildcards.
+    pub resources: Vec<String>,
+    /// The match patterns of the pages that can access the resources.
+    pub matches: Vec<String>,
+}
+
 #[derive(Default, Serialize)]
 /// Represents the information contained in the `manifest.json` file.
 pub struct Manifest {
     present: bool,
+    /// The value of the `manifest_version` property found in the manifest file, if any.
+    pub manifest_version: Option<u64>,
+    /// The add-on name found in the manifest file, if any.
+    pub name: Option<String>,
     /// The add-on ID found in the manifest, if any.
     pub id: Option<String>,
     /// The add-on version found in the manifest file, if any.
@@ -14,6 +43,20 @@ pub struct Manifest {
     /// The value of the `bss.gecko.admin_install_only` property found in the manifest file,
     /// if any.
     pub admin_install_only: Option<bool>,
+    /// The list of permissions declared in the `permissions` property of the manifest file.
+    pub permissions: Vec<String>,
+    /// The list of host permissions declared in the `host_permissions` property of the manifest
+    /// file.
+    pub host_permissions: Vec<String>,
+    /// The content scripts declared in the `content_scripts` property of the manifest file.
+    pub content_scripts: Vec<ContentScript>,
+    /// The resources declared in the `web_accessible_resources` property of the manifest file.
+    pub web_accessible_resources: Vec<WebAccessibleResource>,
+    /// The policies declared in the `content_security_policy` property of the manifest file.
+    pub content_security_policy: Vec<ContentSecurityPolicy>,
+    /// The raw content of the manifest file, used to extract less common properties.
+    #[serde(skip)]
+    pub(crate) data: serde_json::Value,
 }
 
 impl Manifest {
@@ -47,12 +90,45 @@ impl Manifest {
 
                         Manifest {
                             present: true,
+                            manifest_version: data
+                                .get("manifest_version")
+                                .and_then(|value| value.as_u64()),
+                            name: data
+                                .get("name")
+                                .and_then(|value| value.as_str())
+                                .map(|s| s.to_owned()),
                             id,
                             version: data
                                 .get("version")
                                 .and_then(|value| value.as_str())
                                 .map(|s| s.to_owned()),
                             admin_install_only,
+                            permissions: string_array(data.get("permissions")),
+                            host_permissions: string_array(data.get("host_permissions")),
+                            content_scripts: data
+                                .get("content_scripts")
+                                .and_then(|value| value.as_array())
+                                .map(|scripts| {
+                                    scripts
+                                        .iter()
+                                        .map(|script| ContentScript {
+                                            matches: string_array(script.get("matches")),
+                                            exclude_matches: string_array(
+                                                script.get("exclude_matches"),
+                                            ),
+                                            js: string_array(script.get("js")),
+                                            css: string_array(script.get("css")),
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default(),
+                            web_accessible_resources: web_accessible_resources(
+                                data.get("web_accessible_resources"),
+                            ),
+                            content_security_policy: ContentSecurityPolicy::from_manifest(
+                                data.get("content_security_policy"),
+                            ),
+                            data,
                         }
                     }
                     Err(_) => Manifest::default(),
@@ -90,3 +166,41 @@ impl fmt::Display for Manifest {
         )
     }
 }
+
+pub(crate) fn string_array(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|value| value.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|s| s.to_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn web_accessible_resources(value: Option<&serde_json::Value>) -> Vec<WebAccessibleResource> {
+    let Some(values) = value.and_then(|value| value.as_array()) else {
+        return vec![];
+    };
+
+    if values.is_empty() {
+        return vec![];
+    }
+    if values.iter().all(|value| value.is_string()) {
+        return vec![WebAccessibleResource {
+            resources: string_array(value),
+            matches: vec![],
+        }];
+    }
+
+    values
+        .iter()
+        .filter(|value| value.is_object())
+        .map(|value| WebAccessibleResource {
+            resources: string_array(value.get("resources")),
+            matches: string_array(value.get("matches")),
+        })
+        .collect()
+}
@@ -0,0 +1,361 @@
+use super::{wildcard::wildcard_match, HostPermission, PermissionSource, XPI};
+use serde::Serialize;
+use std::{error, fmt, str::FromStr};
+
+const ALL_URLS: &str = "<all_urls>";
+// The schemes matched by `<all_urls>` and the ones matched by the `*` scheme, see:
+// https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/Match_patterns
+const ALL_URLS_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "data", "file"];
+const WILDCARD_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+const SUPPORTED_SCHEMES: &[&str] = &["*", "http", "https", "ws", "wss", "ftp", "data", "file"];
+
+#[derive(Debug, PartialEq)]
+/// Represents the reasons why a match pattern is invalid.
+pub enum MatchPatternError {
+    /// The pattern does not contain `://`.
+    MissingScheme,
+    /// The scheme is not supported.
+    UnsupportedScheme(String),
+    /// The host is empty but the scheme requires one.
+    MissingHost,
+    /// The host contains a `*` that is not the whole host or a leading `*.`.
+    InvalidHost(String),
+    /// The path does not start with a `/`.
+    MissingPath,
+}
+
+impl fmt::Display for MatchPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchPatternError::MissingScheme => write!(f, "missing scheme"),
+            MatchPatternError::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported scheme `{}`", scheme)
+            }
+            MatchPatternError::MissingHost => write!(f, "missing host"),
+            MatchPatternError::InvalidHost(host) => write!(f, "invalid host `{}`", host),
+            MatchPatternError::MissingPath => write!(f, "missing path"),
+        }
+    }
+}
+
+impl error::Error for MatchPatternError {}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Represents a WebExtension match pattern, e.g. `https://*.example.com/*` or `<all_urls>`.
+///
+/// Match patterns are used in content scripts, host permissions and web accessible resources to
+/// describe a set of URLs.
+pub struct MatchPattern {
+    pattern: String,
+    scheme: String,
+    /// The host without the leading `*.` when `subdomains` is `true`, or `*` for any host.
+    host: String,
+    subdomains: bool,
+    port: Option<String>,
+    path: String,
+}
+
+impl MatchPattern {
+    /// Parses and validates a match pattern.
+    pub fn parse(pattern: &str) -> Result<MatchPattern, MatchPatternError> {
+        if pattern == ALL_URLS {
+            return Ok(MatchPattern {
+                pattern: pattern.to_owned(),
+                scheme: ALL_URLS.to_owned(),
+                host: "*".to_owned(),
+                subdomains: false,
+                port: None,
+                path: "/*".to_owned(),
+            });
+        }
+
+        let (scheme, rest) = pattern
+            .split_once("://")
+            .ok_or(MatchPatternError::MissingScheme)?;
+        if !SUPPORTED_SCHEMES.contains(&scheme) {
+            return Err(MatchPatternError::UnsupportedScheme(scheme.to_owned()));
+        }
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => return Err(MatchPatternError::MissingPath),
+        };
+        // Ports are not part of the specification but browsers accept them.
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if is_port(port) || port == "*" => (host, Some(port.to_owned())),
+            _ => (authority, None),
+        };
+
+        if host.is_empty() && scheme != "file" {
+            return Err(MatchPatternError::MissingHost);
+        }
+
+        let (host, subdomains) = match host.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (host, false),
+        };
+        if (subdomains && host.is_empty()) || (host != "*" && host.contains('*')) {
+            return Err(MatchPatternError::InvalidHost(authority.to_owned()));
+        }
+
+        Ok(MatchPattern {
+            pattern: pattern.to_owned(),
+            scheme: scheme.to_owned(),
+            host: host.to_lowercase(),
+            subdomains,
+            port,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Returns the pattern, as it was parsed.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the pattern matches all the hosts, e.g. `<all_urls>` or `*://*/*`.
+    pub fn matches_all_hosts(&self) -> bool {
+        self.host == "*"
+    }
+
+    /// Returns the host of the pattern, e.g. `*.example.com`, or `None` for patterns without a
+    /// host (e.g. `file:///*`).
+    pub fn host(&self) -> Option<String> {
+        match (self.host.as_str(), self.subdomains) {
+            ("", _) => None,
+            (host, true) => Some(format!("*.{}", host)),
+            (host, false) => Some(host.to_owned()),
+        }
+    }
+
+    /// Whether the pattern matches the host and its subdomains, e.g. `*.example.com`.
+    pub fn matches_subdomains(&self) -> bool {
+        self.subdomains
+    }
+
+    /// Returns the scheme of the pattern, e.g. `https` or `*`.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Whether the pattern matches `url`. Invalid URLs are never matched.
+    pub fn matches(&self, url: &str) -> bool {
+        let Some(url) = Url::parse(url) else {
+            return false;
+        };
+
+        let scheme_matches = match self.scheme.as_str() {
+            ALL_URLS => ALL_URLS_SCHEMES.contains(&url.scheme.as_str()),
+            "*" => WILDCARD_SCHEMES.contains(&url.scheme.as_str()),
+            scheme => scheme == url.scheme,
+        };
+        if !scheme_matches {
+            return false;
+        }
+        // `data:` URLs have no host and no path, they can only be matched by `<all_urls>` or a
+        // `data:` pattern.
+        if url.scheme == "data" {
+            return true;
+        }
+
+        let host_matches = if self.host == "*" {
+            true
+        } else if self.subdomains {
+            url.host == self.host || url.host.ends_with(&format!(".{}", self.host))
+        } else {
+            url.host == self.host
+        };
+        let port_matches = match &self.port {
+            Some(port) => port == "*" || url.port.as_ref() == Some(port),
+            None => true,
+        };
+
+        host_matches && port_matches && wildcard_match(&self.path, &url.path)
+    }
+}
+
+impl FromStr for MatchPattern {
+    type Err = MatchPatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        MatchPattern::parse(pattern)
+    }
+}
+
+impl fmt::Display for MatchPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents an invalid match pattern found in a manifest.
+pub struct MatchPatternDiagnostic {
+    /// The manifest property containing the pattern, e.g. `content_scripts[0].matches`.
+    pub property: String,
+    pub pattern: String,
+    /// The reason why the pattern is invalid.
+    pub message: String,
+}
+
+impl fmt::Display for MatchPatternDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.property, self.pattern, self.message)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a content script that is injected into a given URL.
+pub struct MatchedContentScript {
+    /// The index of the content script in the `content_scripts` property of the manifest.
+    pub index: usize,
+    /// The first pattern of the content script that matches the URL.
+    pub pattern: String,
+    pub js: Vec<String>,
+    pub css: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents the content scripts and host permissions of an [`XPI`](`crate::XPI`) that apply to
+/// a given URL.
+pub struct UrlMatches {
+    pub url: String,
+    /// The content scripts matching the URL and not excluded by their `exclude_matches` patterns.
+    pub content_scripts: Vec<MatchedContentScript>,
+    /// The host permissions (from the `permissions` and `host_permissions` properties) matching
+    /// the URL.
+    pub host_permissions: Vec<HostPermission>,
+}
+
+impl XPI {
+    /// Returns the content scripts and host permissions that apply to `url`, or `None` when
+    /// `url` is not a valid URL. Invalid match patterns are ignored.
+    pub fn match_url(&self, url: &str) -> Option<UrlMatches> {
+        Url::parse(url)?;
+
+        let matches = |pattern: &str| MatchPattern::parse(pattern).is_ok_and(|p| p.matches(url));
+
+        let content_scripts = self
+            .manifest
+            .content_scripts
+            .iter()
+            .enumerate()
+            .filter(|(_, script)| !script.exclude_matches.iter().any(|p| matches(p)))
+            .filter_map(|(index, script)| {
+                let pattern = script.matches.iter().find(|p| matches(p))?;
+                Some(MatchedContentScript {
+                    index,
+                    pattern: pattern.to_owned(),
+                    js: script.js.clone(),
+                    css: script.css.clone(),
+                })
+            })
+            .collect();
+
+        let host_permissions = self
+            .permissions
+            .host_permissions
+            .iter()
+            .filter(|permission| permission.source != PermissionSource::ContentScripts)
+            .filter(|permission| matches(&permission.pattern))
+            .cloned()
+            .collect();
+
+        Some(UrlMatches {
+            url: url.to_owned(),
+            content_scripts,
+            host_permissions,
+        })
+    }
+}
+
+impl fmt::Display for UrlMatches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "URL:\n  {}\n\nCONTENT SCRIPTS:", self.url)?;
+        if self.content_scripts.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for script in &self.content_scripts {
+            write!(
+                f,
+                "\n  content_scripts[{}] (matched by {}):",
+                script.index, script.pattern
+            )?;
+            for file in script.js.iter().chain(script.css.iter()) {
+                write!(f, "\n   └── {}", file)?;
+            }
+        }
+
+        write!(f, "\n\nHOST PERMISSIONS:")?;
+        if self.host_permissions.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for permission in &self.host_permissions {
+            write!(f, "\n  {} ({})", permission.pattern, permission.source)?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a URL that are relevant to match patterns.
+struct Url {
+    scheme: String,
+    host: String,
+    port: Option<String>,
+    /// The path including the query string, but without the fragment.
+    path: String,
+}
+
+impl Url {
+    fn parse(url: &str) -> Option<Url> {
+        let url = url.split('#').next().unwrap_or_default();
+
+        if let Some(data) = url.strip_prefix("data:") {
+            return Some(Url {
+                scheme: "data".to_owned(),
+                host: String::new(),
+                port: None,
+                path: data.to_owned(),
+            });
+        }
+
+        let (scheme, rest) = url.split_once("://")?;
+        if scheme.is_empty()
+            || !scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+')
+        {
+            return None;
+        }
+
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let path = if path.starts_with('?') {
+            format!("/{}", path)
+        } else {
+            path.to_owned()
+        };
+        // Drop the user information, if any.
+        let authority = authority.rsplit('@').next().unwrap_or_default();
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if is_port(port) => (host, Some(port.to_owned())),
+            _ => (authority, None),
+        };
+        if host.is_empty() && scheme != "file" {
+            return None;
+        }
+
+        Some(Url {
+            scheme: scheme.to_lowercase(),
+            host: host.to_lowercase(),
+            port,
+            path,
+        })
+    }
+}
+
+fn is_port(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
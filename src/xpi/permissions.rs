@@ -0,0 +1,402 @@
+use super::{Manifest, MatchPattern, MatchPatternDiagnostic};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+/// Represents how much a permission exposes the user, from the least to the most dangerous.
+pub enum RiskLevel {
+    /// No access to user data (e.g., `storage`, `alarms`).
+    Low,
+    /// Access to some user data or to specific websites (e.g., `tabs`, `https://example.com/*`).
+    Medium,
+    /// Access to sensitive user data or browser settings (e.g., `cookies`, `proxy`).
+    High,
+    /// Access to all websites or to programs outside of the browser (e.g., `<all_urls>`,
+    /// `nativeMessaging`).
+    Critical,
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RiskLevel::Low => "LOW",
+                RiskLevel::Medium => "MEDIUM",
+                RiskLevel::High => "HIGH",
+                RiskLevel::Critical => "CRITICAL",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the manifest property in which a host permission has been found.
+pub enum PermissionSource {
+    /// The `permissions` property, which contains the host permissions in Manifest V2.
+    Permissions,
+    /// The `host_permissions` property (Manifest V3).
+    HostPermissions,
+    /// The `matches` property of a content script.
+    ContentScripts,
+}
+
+impl fmt::Display for PermissionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PermissionSource::Permissions => "permissions",
+                PermissionSource::HostPermissions => "host_permissions",
+                PermissionSource::ContentScripts => "content_scripts",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the set of hosts covered by a host permission.
+pub enum HostScope {
+    /// All websites, e.g. `<all_urls>` or `*://*/*`.
+    AllHosts,
+    /// A domain and all its subdomains, e.g. `*://*.example.com/*`.
+    Domain,
+    /// A single host, e.g. `https://example.com/*`.
+    Host,
+    /// Local files, i.e. `file:///*`.
+    Files,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents an API permission, e.g. `storage` or `tabs`.
+pub struct ApiPermission {
+    pub name: String,
+    pub risk: RiskLevel,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// Represents a host permission, i.e. a match pattern granting access to some websites.
+pub struct HostPermission {
+    /// The match pattern, as written in the manifest.
+    pub pattern: String,
+    /// The manifest property in which the pattern has been found.
+    pub source: PermissionSource,
+    /// The host of the match pattern (e.g., `*.example.com`), if any.
+    pub host: Option<String>,
+    pub scope: HostScope,
+    pub risk: RiskLevel,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+/// Represents what an add-on can do according to the permissions declared in its manifest.
+pub struct PermissionAnalysis {
+    /// The API permissions declared in the `permissions` property.
+    pub api_permissions: Vec<ApiPermission>,
+    /// The host permissions, either declared in the `permissions` property (Manifest V2), in the
+    /// `host_permissions` property (Manifest V3) or implied by the content scripts.
+    pub host_permissions: Vec<HostPermission>,
+    /// The warnings shown to the user when the add-on is installed, similar to the ones displayed
+    /// by Firefox.
+    pub warnings: Vec<String>,
+    /// The invalid match patterns found in the manifest. These patterns are ignored by the
+    /// browser, and therefore by this analysis.
+    pub invalid_patterns: Vec<MatchPatternDiagnostic>,
+    /// The host permissions declared in a property that the browser ignores for the manifest
+    /// version, i.e. in `permissions` in Manifest V3 or in `host_permissions` in Manifest V2.
+    /// These permissions are not granted, and therefore not analyzed.
+    pub ignored_host_permissions: Vec<MatchPatternDiagnostic>,
+}
+
+impl PermissionAnalysis {
+    /// Analyzes the permissions declared in a manifest.
+    pub fn new(manifest: &Manifest) -> PermissionAnalysis {
+        let mut analysis = PermissionAnalysis::default();
+        let is_mv3 = manifest.manifest_version == Some(3);
+
+        for permission in &manifest.permissions {
+            if !is_host_permission(permission) {
+                analysis.api_permissions.push(ApiPermission {
+                    name: permission.to_owned(),
+                    risk: api_risk(permission),
+                });
+            } else if is_mv3 {
+                analysis.ignore_host(
+                    "permissions",
+                    permission,
+                    "host permissions must be declared in `host_permissions` in Manifest V3",
+                );
+            } else {
+                // In Manifest V2, host permissions are declared along with the API permissions.
+                analysis.add_host("permissions", permission, PermissionSource::Permissions);
+            }
+        }
+        for permission in &manifest.host_permissions {
+            if is_mv3 {
+                analysis.add_host(
+                    "host_permissions",
+                    permission,
+                    PermissionSource::HostPermissions,
+                );
+            } else {
+                analysis.ignore_host(
+                    "host_permissions",
+                    permission,
+                    "`host_permissions` is only supported in Manifest V3",
+                );
+            }
+        }
+        for (index, script) in manifest.content_scripts.iter().enumerate() {
+            for pattern in &script.matches {
+                analysis.add_host(
+                    &format!("content_scripts[{}].matches", index),
+                    pattern,
+                    PermissionSource::ContentScripts,
+                );
+            }
+            for pattern in &script.exclude_matches {
+                analysis.validate(
+                    &format!("content_scripts[{}].exclude_matches", index),
+                    pattern,
+                );
+            }
+        }
+        for (index, resource) in manifest.web_accessible_resources.iter().enumerate() {
+            for pattern in &resource.matches {
+                analysis.validate(
+                    &format!("web_accessible_resources[{}].matches", index),
+                    pattern,
+                );
+            }
+        }
+
+        analysis.warnings = analysis.compute_warnings();
+        analysis
+    }
+
+    /// Returns the highest risk level of all the permissions, if any.
+    pub fn risk(&self) -> Option<RiskLevel> {
+        self.api_permissions
+            .iter()
+            .map(|permission| permission.risk)
+            .chain(
+                self.host_permissions
+                    .iter()
+                    .map(|permission| permission.risk),
+            )
+            .max()
+    }
+
+    fn validate(&mut self, property: &str, pattern: &str) -> Option<MatchPattern> {
+        match MatchPattern::parse(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(err) => {
+                self.invalid_patterns.push(MatchPatternDiagnostic {
+                    property: property.to_owned(),
+                    pattern: pattern.to_owned(),
+                    message: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    fn ignore_host(&mut self, property: &str, pattern: &str, message: &str) {
+        self.ignored_host_permissions.push(MatchPatternDiagnostic {
+            property: property.to_owned(),
+            pattern: pattern.to_owned(),
+            message: message.to_owned(),
+        });
+    }
+
+    fn add_host(&mut self, property: &str, pattern: &str, source: PermissionSource) {
+        let Some(parsed) = self.validate(property, pattern) else {
+            return;
+        };
+
+        let scope = if parsed.matches_all_hosts() {
+            HostScope::AllHosts
+        } else if parsed.scheme() == "file" {
+            HostScope::Files
+        } else if parsed.matches_subdomains() {
+            HostScope::Domain
+        } else {
+            HostScope::Host
+        };
+        self.host_permissions.push(HostPermission {
+            pattern: pattern.to_owned(),
+            source,
+            host: parsed.host(),
+            scope,
+            risk: match scope {
+                HostScope::AllHosts => RiskLevel::Critical,
+                HostScope::Domain | HostScope::Files => RiskLevel::High,
+                HostScope::Host => RiskLevel::Medium,
+            },
+        });
+    }
+
+    fn compute_warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = vec![];
+        let mut push = |warning: String| {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        };
+
+        if self
+            .host_permissions
+            .iter()
+            .any(|permission| permission.scope == HostScope::AllHosts)
+        {
+            push("Access your data for all websites".to_owned());
+        } else {
+            for permission in &self.host_permissions {
+                let Some(host) = &permission.host else {
+                    continue;
+                };
+                match permission.scope {
+                    HostScope::Domain => push(format!(
+                        "Access your data for sites in the {} domain",
+                        host.trim_start_matches("*.")
+                    )),
+                    HostScope::Host => push(format!("Access your data for {}", host)),
+                    HostScope::AllHosts | HostScope::Files => {}
+                }
+            }
+        }
+
+        for permission in &self.api_permissions {
+            if let Some(warning) = api_warning(&permission.name) {
+                push(warning.to_owned());
+            }
+        }
+
+        warnings
+    }
+}
+
+impl fmt::Display for PermissionAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PERMISSIONS:\n  Risk: {}\n  API Permissions:",
+            self.risk()
+                .map(|risk| risk.to_string())
+                .unwrap_or("NONE".to_owned())
+        )?;
+        if self.api_permissions.is_empty() {
+            write!(f, "\n   └── NONE")?;
+        }
+        for permission in &self.api_permissions {
+            write!(f, "\n   └── {} ({})", permission.name, permission.risk)?;
+        }
+
+        write!(f, "\n  Host Permissions:")?;
+        if self.host_permissions.is_empty() {
+            write!(f, "\n   └── NONE")?;
+        }
+        for permission in &self.host_permissions {
+            write!(
+                f,
+                "\n   └── {} ({}, {})",
+                permission.pattern, permission.risk, permission.source
+            )?;
+        }
+
+        write!(f, "\n  Warnings:")?;
+        if self.warnings.is_empty() {
+            write!(f, "\n   └── NONE")?;
+        }
+        for warning in &self.warnings {
+            write!(f, "\n   └── {}", warning)?;
+        }
+
+        if !self.invalid_patterns.is_empty() {
+            write!(f, "\n  Invalid Match Patterns:")?;
+        }
+        for diagnostic in &self.invalid_patterns {
+            write!(f, "\n   └── {}", diagnostic)?;
+        }
+
+        if !self.ignored_host_permissions.is_empty() {
+            write!(f, "\n  Ignored Host Permissions:")?;
+        }
+        for diagnostic in &self.ignored_host_permissions {
+            write!(f, "\n   └── {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_host_permission(permission: &str) -> bool {
+    permission == "<all_urls>" || permission.contains("://")
+}
+
+fn api_risk(permission: &str) -> RiskLevel {
+    match permission {
+        "nativeMessaging" | "debugger" => RiskLevel::Critical,
+        "browserSettings"
+        | "browsingData"
+        | "clipboardRead"
+        | "cookies"
+        | "declarativeNetRequest"
+        | "devtools"
+        | "downloads"
+        | "find"
+        | "history"
+        | "management"
+        | "pkcs11"
+        | "privacy"
+        | "proxy"
+        | "webRequestBlocking" => RiskLevel::High,
+        "bookmarks"
+        | "clipboardWrite"
+        | "contentSettings"
+        | "declarativeNetRequestFeedback"
+        | "downloads.open"
+        | "geolocation"
+        | "scripting"
+        | "sessions"
+        | "tabHide"
+        | "tabs"
+        | "topSites"
+        | "webNavigation"
+        | "webRequest" => RiskLevel::Medium,
+        _ => RiskLevel::Low,
+    }
+}
+
+/// Returns the warning displayed by Firefox for an API permission, if any.
+fn api_warning(permission: &str) -> Option<&'static str> {
+    Some(match permission {
+        "bookmarks" => "Read and modify bookmarks",
+        "browserSettings" => "Read and modify browser settings",
+        "browsingData" => "Clear recent browsing history, cookies, and related data",
+        "clipboardRead" => "Get data from the clipboard",
+        "clipboardWrite" => "Input data to the clipboard",
+        "declarativeNetRequest" => "Block content on any page",
+        "declarativeNetRequestFeedback" => "Read your browsing history",
+        "devtools" => "Extend developer tools to access your data in open tabs",
+        "downloads" => "Download files and read and modify the browser’s download history",
+        "downloads.open" => "Open files downloaded to your computer",
+        "find" => "Read the text of all open tabs",
+        "geolocation" => "Access your location",
+        "history" | "topSites" => "Access browsing history",
+        "management" => "Monitor extension usage and manage themes",
+        "nativeMessaging" => "Exchange messages with programs other than Firefox",
+        "notifications" => "Display notifications to you",
+        "pkcs11" => "Provide cryptographic authentication services",
+        "privacy" => "Read and modify privacy settings",
+        "proxy" => "Control browser proxy settings",
+        "sessions" => "Access recently closed tabs",
+        "tabHide" => "Hide and show browser tabs",
+        "tabs" => "Access browser tabs",
+        "webNavigation" => "Access browser activity during navigation",
+        _ => return None,
+    })
+}
@@ -0,0 +1,311 @@
+use super::{wildcard::wildcard_match, Environment, Signature, SignatureKind, XPI};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Represents the different types of signatures that can be found in an XPI file.
+pub enum SignatureFormat {
+    /// A PKCS#7 signature.
+    Pkcs7,
+    /// A COSE signature.
+    Cose,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// Represents a set of acceptance rules that an [`XPI`](`crate::XPI`) should satisfy.
+///
+/// A policy is usually loaded from a JSON file. Each property is optional and a rule is only
+/// evaluated when its property is set:
+///
+/// ```json
+/// {
+///   "required_signatures": ["pkcs7", "cose"],
+///   "allowed_kinds": ["regular"],
+///   "allowed_environments": ["production"],
+///   "allowed_pkcs7_algorithms": ["SHA-1", "SHA-256"],
+///   "allowed_cose_algorithms": ["ES256"],
+///   "allowed_permissions": ["storage", "activeTab"],
+///   "allowed_ids": ["*@mozilla.com"],
+///   "min_certificate_lifetime_days": 30
+/// }
+/// ```
+pub struct Policy {
+    /// The signatures that must be present in the XPI.
+    pub required_signatures: Option<Vec<SignatureFormat>>,
+    /// The kinds of signatures (regular, privileged, system) that are accepted.
+    pub allowed_kinds: Option<Vec<SignatureKind>>,
+    /// The signing environments that are accepted.
+    pub allowed_environments: Option<Vec<Environment>>,
+    /// The PKCS#7 digest algorithms that are accepted.
+    pub allowed_pkcs7_algorithms: Option<Vec<String>>,
+    /// The COSE algorithms that are accepted.
+    pub allowed_cose_algorithms: Option<Vec<String>>,
+    /// The permissions (including host permissions) that the add-on is allowed to request.
+    pub allowed_permissions: Option<Vec<String>>,
+    /// The patterns that the add-on ID must match. A `*` matches any sequence of characters.
+    pub allowed_ids: Option<Vec<String>>,
+    /// The minimum number of days before any certificate of a signature expires.
+    pub min_certificate_lifetime_days: Option<u64>,
+}
+
+#[derive(Serialize)]
+/// Represents the outcome of a single rule of a [`Policy`].
+pub struct RuleResult {
+    /// The name of the rule, which is the name of the property in the policy file.
+    pub rule: String,
+    /// Whether the XPI satisfies the rule.
+    pub passed: bool,
+    /// A human-readable explanation of the outcome.
+    pub message: String,
+}
+
+#[derive(Serialize)]
+/// Represents the outcome of the evaluation of a [`Policy`] against an [`XPI`](`crate::XPI`).
+pub struct PolicyReport {
+    /// The results of each rule, in the order in which they have been evaluated.
+    pub results: Vec<RuleResult>,
+}
+
+impl PolicyReport {
+    /// Whether all the rules of the policy are satisfied.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+}
+
+impl fmt::Display for PolicyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "POLICY: {}",
+            if self.passed() { "PASSED" } else { "FAILED" }
+        )?;
+        for result in &self.results {
+            write!(
+                f,
+                "\n  [{}] {}: {}",
+                if result.passed { "PASS" } else { "FAIL" },
+                result.rule,
+                result.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Policy {
+    /// Parses a policy from a JSON document.
+    pub fn from_json(json: &str) -> Result<Policy, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Evaluates the policy against an [`XPI`](`crate::XPI`) using the current time.
+    pub fn evaluate(&self, xpi: &XPI) -> PolicyReport {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        self.evaluate_at(xpi, now)
+    }
+
+    /// Evaluates the policy against an [`XPI`](`crate::XPI`), using `now` (a duration since the
+    /// Unix epoch) to compute the remaining lifetime of the certificates.
+    pub fn evaluate_at(&self, xpi: &XPI, now: Duration) -> PolicyReport {
+        let mut results = vec![];
+        let signatures = present_signatures(xpi);
+
+        if let Some(required) = &self.required_signatures {
+            let missing: Vec<&str> = required
+                .iter()
+                .filter(|format| match format {
+                    SignatureFormat::Pkcs7 => !xpi.signatures.pkcs7.exists(),
+                    SignatureFormat::Cose => !xpi.signatures.cose.exists(),
+                })
+                .map(|format| match format {
+                    SignatureFormat::Pkcs7 => "PKCS7",
+                    SignatureFormat::Cose => "COSE",
+                })
+                .collect();
+
+            results.push(if missing.is_empty() {
+                RuleResult::pass("required_signatures", "all required signatures are present")
+            } else {
+                RuleResult::fail(
+                    "required_signatures",
+                    format!("missing signatures: {}", missing.join(", ")),
+                )
+            });
+        }
+
+        if let Some(allowed) = &self.allowed_kinds {
+            results.push(check_signatures(
+                "allowed_kinds",
+                &signatures,
+                |signature| {
+                    let kind = signature.kind();
+                    (allowed.contains(&kind), kind.to_string())
+                },
+            ));
+        }
+
+        if let Some(allowed) = &self.allowed_environments {
+            results.push(check_signatures(
+                "allowed_environments",
+                &signatures,
+                |signature| {
+                    let env = signature.env();
+                    (allowed.contains(&env), env.to_string())
+                },
+            ));
+        }
+
+        if let Some(allowed) = &self.allowed_pkcs7_algorithms {
+            results.push(check_algorithm(
+                "allowed_pkcs7_algorithms",
+                &xpi.signatures.pkcs7,
+                allowed,
+            ));
+        }
+
+        if let Some(allowed) = &self.allowed_cose_algorithms {
+            results.push(check_algorithm(
+                "allowed_cose_algorithms",
+                &xpi.signatures.cose,
+                allowed,
+            ));
+        }
+
+        if let Some(allowed) = &self.allowed_permissions {
+            let disallowed: Vec<&str> = xpi
+                .manifest
+                .permissions
+                .iter()
+                .chain(xpi.manifest.host_permissions.iter())
+                .filter(|permission| !allowed.contains(permission))
+                .map(|permission| permission.as_str())
+                .collect();
+
+            results.push(if disallowed.is_empty() {
+                RuleResult::pass("allowed_permissions", "all permissions are allowed")
+            } else {
+                RuleResult::fail(
+                    "allowed_permissions",
+                    format!("disallowed permissions: {}", disallowed.join(", ")),
+                )
+            });
+        }
+
+        if let Some(patterns) = &self.allowed_ids {
+            results.push(match &xpi.manifest.id {
+                Some(id) if patterns.iter().any(|pattern| wildcard_match(pattern, id)) => {
+                    RuleResult::pass("allowed_ids", format!("{} is allowed", id))
+                }
+                Some(id) => RuleResult::fail("allowed_ids", format!("{} is not allowed", id)),
+                None => RuleResult::fail("allowed_ids", "no add-on ID in the manifest"),
+            });
+        }
+
+        if let Some(days) = self.min_certificate_lifetime_days {
+            let min_lifetime = Duration::from_secs(days * SECONDS_PER_DAY);
+            results.push(check_signatures(
+                "min_certificate_lifetime_days",
+                &signatures,
+                |signature| match signature
+                    .certificates
+                    .iter()
+                    .map(|cert| cert.end_date.to_unix_duration())
+                    .min()
+                {
+                    Some(end_date) => {
+                        let remaining = end_date.saturating_sub(now);
+                        (
+                            remaining >= min_lifetime,
+                            format!("{} day(s) remaining", remaining.as_secs() / SECONDS_PER_DAY),
+                        )
+                    }
+                    None => (false, "no certificate".to_owned()),
+                },
+            ));
+        }
+
+        PolicyReport { results }
+    }
+}
+
+impl RuleResult {
+    fn pass(rule: &str, message: impl Into<String>) -> RuleResult {
+        RuleResult {
+            rule: rule.to_owned(),
+            passed: true,
+            message: message.into(),
+        }
+    }
+
+    fn fail(rule: &str, message: impl Into<String>) -> RuleResult {
+        RuleResult {
+            rule: rule.to_owned(),
+            passed: false,
+            message: message.into(),
+        }
+    }
+}
+
+fn present_signatures(xpi: &XPI) -> Vec<(&'static str, &Signature)> {
+    [
+        ("PKCS7", &xpi.signatures.pkcs7),
+        ("COSE", &xpi.signatures.cose),
+    ]
+    .into_iter()
+    .filter(|(_, signature)| signature.exists())
+    .collect()
+}
+
+/// Applies `check` to each signature and returns a single result for the rule. The check returns
+/// whether the signature satisfies the rule along with a description of the checked value.
+fn check_signatures<F>(rule: &str, signatures: &[(&str, &Signature)], check: F) -> RuleResult
+where
+    F: Fn(&Signature) -> (bool, String),
+{
+    if signatures.is_empty() {
+        return RuleResult::fail(rule, "no signature found");
+    }
+
+    let mut passed = true;
+    let mut details = vec![];
+    for (name, signature) in signatures {
+        let (ok, value) = check(signature);
+        passed &= ok;
+        details.push(format!("{}: {}", name, value));
+    }
+
+    RuleResult {
+        rule: rule.to_owned(),
+        passed,
+        message: details.join(", "),
+    }
+}
+
+fn check_algorithm(rule: &str, signature: &Signature, allowed: &[String]) -> RuleResult {
+    if !signature.exists() {
+        return RuleResult::fail(rule, "no signature found");
+    }
+
+    match signature
+        .algorithm
+        .as_ref()
+        .map(|algorithm| algorithm.to_string())
+    {
+        Some(algorithm) if allowed.contains(&algorithm) => {
+            RuleResult::pass(rule, format!("{} is allowed", algorithm))
+        }
+        Some(algorithm) => RuleResult::fail(rule, format!("{} is not allowed", algorithm)),
+        None => RuleResult::fail(rule, "unknown algorithm"),
+    }
+}
@@ -0,0 +1,206 @@
+use super::{manifest::string_array, Entry, Manifest};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the outcome of the resolution of a path referenced in the manifest.
+pub enum ReferenceStatus {
+    /// The file exists in the XPI.
+    Found,
+    /// The file does not exist in the XPI.
+    Missing,
+    /// The file exists in the XPI but with a different case, which only works on case-insensitive
+    /// file systems.
+    CaseMismatch,
+}
+
+impl fmt::Display for ReferenceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReferenceStatus::Found => "FOUND",
+                ReferenceStatus::Missing => "MISSING",
+                ReferenceStatus::CaseMismatch => "CASE MISMATCH",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a file referenced in the manifest.
+pub struct Reference {
+    /// The manifest property containing the path, e.g. `background.scripts[0]`.
+    pub property: String,
+    /// The path, as written in the manifest.
+    pub path: String,
+    pub status: ReferenceStatus,
+    /// The path of the file found in the XPI when the case does not match.
+    pub actual_path: Option<String>,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.status, self.property, self.path)?;
+        if let Some(actual_path) = &self.actual_path {
+            write!(f, " (found {})", actual_path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Reference {
+    /// Resolves the paths referenced in the manifest against the entries of the XPI.
+    pub(crate) fn check_all(manifest: &Manifest, entries: &[Entry]) -> Vec<Reference> {
+        if !manifest.exists() {
+            return vec![];
+        }
+
+        let data = &manifest.data;
+        let mut paths: Vec<(String, String)> = vec![];
+        let mut add = |property: String, path: Option<&str>| {
+            if let Some(path) = path {
+                paths.push((property, path.to_owned()));
+            }
+        };
+
+        let background = data.get("background");
+        for (index, script) in string_array(background.and_then(|b| b.get("scripts")))
+            .iter()
+            .enumerate()
+        {
+            add(format!("background.scripts[{}]", index), Some(script));
+        }
+        for name in ["service_worker", "page"] {
+            add(
+                format!("background.{}", name),
+                str_value(background.and_then(|b| b.get(name))),
+            );
+        }
+
+        for (index, script) in manifest.content_scripts.iter().enumerate() {
+            for (kind, files) in [("js", &script.js), ("css", &script.css)] {
+                for (file_index, file) in files.iter().enumerate() {
+                    add(
+                        format!("content_scripts[{}].{}[{}]", index, kind, file_index),
+                        Some(file),
+                    );
+                }
+            }
+        }
+
+        if let Some(icons) = data.get("icons").and_then(|icons| icons.as_object()) {
+            for (size, icon) in icons {
+                add(format!("icons.{}", size), icon.as_str());
+            }
+        }
+
+        for action in ["action", "browser_action", "page_action"] {
+            add(
+                format!("{}.default_popup", action),
+                str_value(data.get(action).and_then(|a| a.get("default_popup"))),
+            );
+        }
+        add(
+            "options_ui.page".to_owned(),
+            str_value(data.get("options_ui").and_then(|o| o.get("page"))),
+        );
+        add(
+            "options_page".to_owned(),
+            str_value(data.get("options_page")),
+        );
+
+        if let Some(images) = data
+            .get("theme")
+            .and_then(|theme| theme.get("images"))
+            .and_then(|images| images.as_object())
+        {
+            for (name, value) in images {
+                match value {
+                    Value::Array(values) => {
+                        for (index, value) in values.iter().enumerate() {
+                            add(format!("theme.images.{}[{}]", name, index), value.as_str());
+                        }
+                    }
+                    value => add(format!("theme.images.{}", name), value.as_str()),
+                }
+            }
+        }
+
+        // Each locale must have a `messages.json` file, including the default one.
+        let mut locales: Vec<&str> = vec![];
+        if let Some(default_locale) = str_value(data.get("default_locale")) {
+            locales.push(default_locale);
+        }
+        for entry in entries {
+            let locale = entry
+                .name
+                .strip_prefix("_locales/")
+                .and_then(|path| path.split_once('/'))
+                .map(|(locale, _)| locale);
+            if let Some(locale) = locale.filter(|locale| !locales.contains(locale)) {
+                locales.push(locale);
+            }
+        }
+        for locale in locales {
+            paths.push((
+                format!("_locales.{}", locale),
+                format!("_locales/{}/messages.json", locale),
+            ));
+        }
+
+        paths
+            .into_iter()
+            .filter_map(|(property, path)| {
+                let normalized = normalize(&path)?;
+                let (status, actual_path) = if entries.iter().any(|entry| entry.name == normalized)
+                {
+                    (ReferenceStatus::Found, None)
+                } else if let Some(entry) = entries
+                    .iter()
+                    .find(|entry| entry.name.eq_ignore_ascii_case(&normalized))
+                {
+                    (ReferenceStatus::CaseMismatch, Some(entry.name.to_owned()))
+                } else {
+                    (ReferenceStatus::Missing, None)
+                };
+
+                Some(Reference {
+                    property,
+                    path,
+                    status,
+                    actual_path,
+                })
+            })
+            .collect()
+    }
+}
+
+fn str_value(value: Option<&Value>) -> Option<&str> {
+    value.and_then(|value| value.as_str())
+}
+
+/// Converts a path referenced in the manifest to the name of an entry in the archive. `None` is
+/// returned for URLs, which do not reference files of the XPI.
+pub(crate) fn normalize(path: &str) -> Option<String> {
+    if path.contains("://") || path.starts_with("data:") {
+        return None;
+    }
+
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let mut parts: Vec<&str> = vec![];
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    Some(parts.join("/"))
+}
@@ -0,0 +1,374 @@
+use super::{
+    detect_libraries,
+    source_maps::{find_bundler, find_source_mapping_urls, is_source_map},
+    Bundle, DetectedLibrary, Error, SourceMap, SourceMappingUrl,
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Cursor, Read},
+};
+use zip::ZipArchive;
+
+/// The extensions of the files that are scanned.
+const SCANNED_EXTENSIONS: &[&str] = &[".js", ".mjs", ".html", ".htm"];
+/// Lines longer than this (in characters) are reported, they usually indicate minified or
+/// obfuscated code.
+const LONG_LINE_LENGTH: usize = 1000;
+/// The minimum length of a line for its entropy to be computed, short lines are not meaningful.
+const ENTROPY_MIN_LENGTH: usize = 256;
+/// The Shannon entropy (in bits per character) above which a line is reported. Regular (even
+/// minified) code is usually below 5.6, while base64 blobs are close to 6.
+const ENTROPY_THRESHOLD: f64 = 5.8;
+/// The maximum length of the snippets included in the findings.
+const SNIPPET_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the kinds of risky constructs reported by the scanner.
+pub enum FindingKind {
+    /// A call to `eval()`.
+    Eval,
+    /// A call to the `Function` constructor.
+    NewFunction,
+    /// An assignment to `innerHTML`.
+    InnerHtmlAssignment,
+    /// A call to `document.write()` or `document.writeln()`.
+    DocumentWrite,
+    /// A `<script>` element loading a remote script.
+    RemoteScript,
+    /// A call to `setTimeout()` or `setInterval()` with a string.
+    SetTimeoutString,
+    /// A very long line.
+    LongLine,
+    /// A line with a high entropy, e.g. a large encoded blob.
+    HighEntropy,
+}
+
+impl fmt::Display for FindingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FindingKind::Eval => "eval()",
+                FindingKind::NewFunction => "new Function()",
+                FindingKind::InnerHtmlAssignment => "innerHTML assignment",
+                FindingKind::DocumentWrite => "document.write()",
+                FindingKind::RemoteScript => "remote <script>",
+                FindingKind::SetTimeoutString => "setTimeout() with a string",
+                FindingKind::LongLine => "long line",
+                FindingKind::HighEntropy => "high entropy",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a risky construct found in a file.
+pub struct Finding {
+    /// The path of the file in the archive.
+    pub path: String,
+    /// The line number, starting at 1.
+    pub line: usize,
+    pub kind: FindingKind,
+    /// The beginning of the line (or of the construct), for context.
+    pub snippet: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ({})",
+            self.path, self.line, self.kind, self.snippet
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+/// Represents the result of a static scan of the files stored in an XPI.
+///
+/// Besides the risky constructs, the scan identifies the well-known libraries bundled in the XPI
+/// using a local database of banners and hashes.
+///
+/// The scan only looks for patterns, which gives hints to reviewers but can lead to false
+/// positives (e.g., in comments) and false negatives (e.g., in obfuscated code).
+pub struct ScanReport {
+    /// The risky constructs found in the JavaScript and HTML files.
+    pub findings: Vec<Finding>,
+    /// The well-known libraries found in the JavaScript files.
+    pub libraries: Vec<DetectedLibrary>,
+    /// The source maps (`.map` files) stored in the XPI.
+    pub source_maps: Vec<SourceMap>,
+    /// The `sourceMappingURL` comments found in the JavaScript and CSS files.
+    pub source_mapping_urls: Vec<SourceMappingUrl>,
+    /// The JavaScript files generated by a bundler (e.g., webpack).
+    pub bundles: Vec<Bundle>,
+}
+
+impl ScanReport {
+    /// Scans the files stored in an instance of
+    /// [`ZipArchive`](https://docs.rs/zip/latest/zip/read/struct.ZipArchive.html).
+    pub fn new<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> ScanReport {
+        let mut report = ScanReport::default();
+        let names: Vec<String> = archive.file_names().map(|name| name.to_owned()).collect();
+
+        for index in 0..archive.len() {
+            let Ok(mut file) = archive.by_index(index) else {
+                continue;
+            };
+            let path = file.name().to_owned();
+            let lowercase_path = path.to_ascii_lowercase();
+            let is_javascript = lowercase_path.ends_with(".js") || lowercase_path.ends_with(".mjs");
+            let is_css = lowercase_path.ends_with(".css");
+            let is_source_map = is_source_map(&path);
+            if file.is_dir() || !(is_scanned(&path) || is_css || is_source_map) {
+                continue;
+            }
+
+            let mut content = vec![];
+            if file.read_to_end(&mut content).is_err() {
+                continue;
+            }
+
+            if is_source_map {
+                report.source_maps.extend(SourceMap::parse(&path, &content));
+                continue;
+            }
+
+            let text = String::from_utf8_lossy(&content);
+            if is_javascript || is_css {
+                report
+                    .source_mapping_urls
+                    .extend(find_source_mapping_urls(&path, &text, |name| {
+                        names.iter().any(|other| other == name)
+                    }));
+            }
+            if is_css {
+                continue;
+            }
+
+            report.findings.extend(scan_source(&path, &text));
+            if is_javascript {
+                report.libraries.extend(detect_libraries(&path, &content));
+                if let Some(bundler) = find_bundler(&text) {
+                    report.bundles.push(Bundle { path, bundler });
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Scans the files stored in the content of an XPI file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<ScanReport, Error> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+        Ok(ScanReport::new(&mut archive))
+    }
+}
+
+impl fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FINDINGS:")?;
+        if self.findings.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for finding in &self.findings {
+            write!(f, "\n  {}", finding)?;
+        }
+
+        write!(f, "\n\nLIBRARIES:")?;
+        if self.libraries.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for library in &self.libraries {
+            write!(f, "\n  {}", library)?;
+        }
+
+        write!(f, "\n\nBUNDLES:")?;
+        if self.bundles.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for bundle in &self.bundles {
+            write!(f, "\n  {}: {}", bundle.path, bundle.bundler)?;
+        }
+
+        write!(f, "\n\nSOURCE MAPPING URLS:")?;
+        if self.source_mapping_urls.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for url in &self.source_mapping_urls {
+            write!(f, "\n  {}", url)?;
+        }
+
+        write!(f, "\n\nSOURCE MAPS:")?;
+        if self.source_maps.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for source_map in &self.source_maps {
+            write!(f, "\n  {}", source_map)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_scanned(path: &str) -> bool {
+    let path = path.to_ascii_lowercase();
+    SCANNED_EXTENSIONS
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// Scans the content of a JavaScript or HTML file, `path` is only used to build the findings.
+pub fn scan_source(path: &str, source: &str) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let mut push = |kind, offset: usize| {
+            findings.push(Finding {
+                path: path.to_owned(),
+                line: index + 1,
+                kind,
+                snippet: snippet(&line[offset..]),
+            })
+        };
+
+        for offset in find_calls(line, "eval") {
+            push(FindingKind::Eval, offset);
+        }
+        for offset in find_new_function(line) {
+            push(FindingKind::NewFunction, offset);
+        }
+        for offset in find_inner_html_assignments(line) {
+            push(FindingKind::InnerHtmlAssignment, offset);
+        }
+        for name in ["document.write", "document.writeln"] {
+            for offset in find_calls(line, name) {
+                push(FindingKind::DocumentWrite, offset);
+            }
+        }
+        for offset in find_remote_scripts(line) {
+            push(FindingKind::RemoteScript, offset);
+        }
+        for name in ["setTimeout", "setInterval"] {
+            for offset in find_calls(line, name) {
+                let arguments = line[offset + name.len()..].trim_start()[1..].trim_start();
+                if arguments.starts_with(['"', '\'', '`']) {
+                    push(FindingKind::SetTimeoutString, offset);
+                }
+            }
+        }
+
+        let length = line.chars().count();
+        if length > LONG_LINE_LENGTH {
+            push(FindingKind::LongLine, 0);
+        }
+        if length >= ENTROPY_MIN_LENGTH && entropy(line) >= ENTROPY_THRESHOLD {
+            push(FindingKind::HighEntropy, 0);
+        }
+    }
+
+    findings
+}
+
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(SNIPPET_LENGTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_owned(),
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Returns the offsets of the occurrences of `name` that are not part of a longer identifier.
+fn find_identifier(line: &str, name: &str) -> Vec<usize> {
+    line.match_indices(name)
+        .map(|(offset, _)| offset)
+        .filter(|offset| {
+            let before = line[..*offset].chars().next_back();
+            let after = line[offset + name.len()..].chars().next();
+            !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+        })
+        .collect()
+}
+
+/// Returns the offsets of the calls to `name`, i.e. `name` followed by an opening parenthesis.
+fn find_calls(line: &str, name: &str) -> Vec<usize> {
+    find_identifier(line, name)
+        .into_iter()
+        .filter(|offset| line[offset + name.len()..].trim_start().starts_with('('))
+        .collect()
+}
+
+fn find_new_function(line: &str) -> Vec<usize> {
+    find_identifier(line, "new")
+        .into_iter()
+        .filter(|offset| {
+            let rest = &line[offset + "new".len()..];
+            let trimmed = rest.trim_start();
+            trimmed.len() < rest.len()
+                && trimmed.starts_with("Function")
+                && !trimmed["Function".len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_identifier_char)
+        })
+        .collect()
+}
+
+fn find_inner_html_assignments(line: &str) -> Vec<usize> {
+    find_identifier(line, "innerHTML")
+        .into_iter()
+        .filter(|offset| {
+            let rest = line[offset + "innerHTML".len()..].trim_start();
+            (rest.starts_with('=') && !rest.starts_with("==")) || rest.starts_with("+=")
+        })
+        .collect()
+}
+
+/// Returns the offsets of the `<script>` elements with a `src` attribute pointing to a remote
+/// URL.
+fn find_remote_scripts(line: &str) -> Vec<usize> {
+    let lowercase = line.to_ascii_lowercase();
+
+    lowercase
+        .match_indices("<script")
+        .map(|(offset, _)| offset)
+        .filter(|offset| {
+            let tag = &lowercase[*offset..];
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let Some(src) = tag.find("src") else {
+                return false;
+            };
+            let Some(value) = tag[src + "src".len()..].trim_start().strip_prefix('=') else {
+                return false;
+            };
+            let value = value.trim_start().trim_start_matches(['"', '\'']);
+            value.starts_with("http://") || value.starts_with("https://") || value.starts_with("//")
+        })
+        .collect()
+}
+
+/// Computes the Shannon entropy of `text`, in bits per character.
+fn entropy(text: &str) -> f64 {
+    let mut counts = HashMap::new();
+    let mut total = 0;
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+        total += 1;
+    }
+
+    counts
+        .values()
+        .map(|count| {
+            let p = *count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
@@ -1,28 +1,59 @@
-use super::cose_ish::CoseSign;
+use super::{
+    consistency::check_consistency, cose_ish::CoseSign, entries::to_hex, Algorithm, CoseQuirk,
+    DigestAlgorithm, SignatureAlgorithm, SignatureMismatch,
+};
 use cms::cert::{
     x509,
     x509::{
         attr::AttributeTypeAndValue,
         certificate::TbsCertificateInner,
         der::{
-            asn1::{PrintableStringRef, TeletexStringRef, UtcTime, Utf8StringRef},
+            asn1::{
+                ObjectIdentifier, OctetString, PrintableStringRef, TeletexStringRef, UtcTime,
+                Utf8StringRef,
+            },
             Decode, Encode, Tag, Tagged,
         },
+        ext::pkix::SubjectKeyIdentifier,
         Certificate,
     },
     CertificateChoices,
 };
 use cms::content_info::ContentInfo;
-use cms::signed_data::SignedData;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
 use const_oid::db::{
     rfc4519::{COMMON_NAME, ORGANIZATIONAL_UNIT_NAME},
-    rfc5912::{ID_SHA_1, ID_SHA_256},
+    rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER,
+    rfc5911::{ID_CONTENT_TYPE, ID_MESSAGE_DIGEST, ID_SIGNING_TIME},
+    DB,
 };
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 use std::convert::{From, TryInto};
 use std::{fmt, io, io::Read, time::Duration};
 use zip::ZipArchive;
 
+/// The manifest of a PKCS#7 signature, which lists the digests of the files.
+pub(crate) const PKCS7_MANIFEST_FILE: &str = "META-INF/manifest.mf";
+/// The signature file of a PKCS#7 signature, which contains the digest of the manifest.
+pub(crate) const PKCS7_SF_FILE: &str = "META-INF/mozilla.sf";
+/// The PKCS#7 signature of the signature file.
+pub(crate) const PKCS7_SIGNATURE_FILE: &str = "META-INF/mozilla.rsa";
+/// The manifest of a COSE signature, which lists the digests of the files.
+pub(crate) const COSE_MANIFEST_FILE: &str = "META-INF/cose.manifest";
+/// The COSE signature of the manifest.
+pub(crate) const COSE_SIGNATURE_FILE: &str = "META-INF/cose.sig";
+
+/// The files of a PKCS#7 signature: the manifest, the signature file and the signature.
+pub(crate) const PKCS7_FILES: [&str; 3] =
+    [PKCS7_MANIFEST_FILE, PKCS7_SF_FILE, PKCS7_SIGNATURE_FILE];
+/// The files of a COSE signature: the manifest and the signature.
+pub(crate) const COSE_FILES: [&str; 2] = [COSE_MANIFEST_FILE, COSE_SIGNATURE_FILE];
+
+/// Whether a file of an archive belongs to a PKCS#7 or COSE signature.
+pub(crate) fn is_signature_file(name: &str) -> bool {
+    PKCS7_FILES.contains(&name) || COSE_FILES.contains(&name)
+}
+
 #[derive(Debug, PartialEq)]
 /// Represents a date in a certificate.
 pub struct Date(x509::time::Time);
@@ -33,6 +64,11 @@ impl Date {
             UtcTime::from_unix_duration(duration).expect("failed to make UtcTime"),
         ))
     }
+
+    /// Returns the date as a duration since the Unix epoch.
+    pub fn to_unix_duration(&self) -> Duration {
+        self.0.to_unix_duration()
+    }
 }
 
 impl Default for Date {
@@ -57,7 +93,8 @@ impl fmt::Display for Date {
     }
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Environment {
     #[default]
     Unknown,
@@ -86,6 +123,8 @@ impl fmt::Display for Environment {
 pub struct CertificateInfo {
     pub common_name: String,
     pub organizational_unit: String,
+    /// The common name of the issuer of the certificate.
+    pub issuer_common_name: String,
     pub end_date: Date,
 }
 
@@ -136,9 +175,19 @@ impl From<&TbsCertificateInner> for CertificateInfo {
             }
         }
 
+        let issuer_common_name = tbs_cert
+            .issuer
+            .0
+            .iter()
+            .rev()
+            .filter_map(|rdn| rdn.0.get(0))
+            .find(|atv| atv.oid == COMMON_NAME)
+            .map_or("N/A".to_string(), atv_to_string);
+
         CertificateInfo {
             common_name,
             organizational_unit,
+            issuer_common_name,
             end_date: Date(tbs_cert.validity.not_after),
         }
     }
@@ -156,7 +205,8 @@ impl fmt::Display for CertificateInfo {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 /// Represents the kind of signature found in the XPI.
 pub enum SignatureKind {
     /// The XPI has been signed as a regular add-on.
@@ -181,12 +231,139 @@ impl fmt::Display for SignatureKind {
     }
 }
 
+#[derive(Serialize)]
+/// Represents a signer of a PKCS#7 signature, i.e. a `SignerInfo` structure.
+pub struct Signer {
+    /// The issuer of the signer certificate, when the signer is identified by the issuer and
+    /// serial number of its certificate.
+    pub issuer: Option<String>,
+    /// The serial number of the signer certificate, when the signer is identified by the issuer
+    /// and serial number of its certificate.
+    pub serial_number: Option<String>,
+    /// The subject key identifier of the signer certificate (hex-encoded), when the signer is
+    /// identified by it.
+    pub subject_key_identifier: Option<String>,
+    pub digest_algorithm: DigestAlgorithm,
+    pub signature_algorithm: SignatureAlgorithm,
+    /// The `signingTime` signed attribute, if any.
+    pub signing_time: Option<Date>,
+    /// The `contentType` signed attribute, if any.
+    pub content_type: Option<String>,
+    /// The `messageDigest` signed attribute (hex-encoded), if any.
+    pub message_digest: Option<String>,
+    /// The signer certificate, when it is embedded in the signature.
+    pub certificate: Option<CertificateInfo>,
+}
+
+impl Signer {
+    fn new(info: &SignerInfo, certificates: &[&Certificate]) -> Self {
+        let digest_algorithm = DigestAlgorithm::from_oid(&info.digest_alg.oid);
+        let mut signer = Signer {
+            issuer: None,
+            serial_number: None,
+            subject_key_identifier: None,
+            signature_algorithm: SignatureAlgorithm::from_algorithm_identifier(
+                &info.signature_algorithm,
+                &digest_algorithm,
+            ),
+            digest_algorithm,
+            signing_time: None,
+            content_type: None,
+            message_digest: None,
+            certificate: None,
+        };
+
+        match &info.sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => {
+                signer.issuer = Some(id.issuer.to_string());
+                signer.serial_number = Some(id.serial_number.to_string());
+            }
+            SignerIdentifier::SubjectKeyIdentifier(id) => {
+                signer.subject_key_identifier = Some(to_hex(id.0.as_bytes()));
+            }
+        };
+        signer.certificate = find_certificate(info, certificates).map(|cert| cert.into());
+
+        for attr in info.signed_attrs.iter().flat_map(|attrs| attrs.iter()) {
+            let Some(value) = attr.values.get(0) else {
+                continue;
+            };
+            match attr.oid {
+                ID_SIGNING_TIME => {
+                    signer.signing_time = value
+                        .to_der()
+                        .and_then(|der| x509::time::Time::from_der(&der))
+                        .ok()
+                        .map(Date);
+                }
+                ID_CONTENT_TYPE => {
+                    signer.content_type = value
+                        .decode_as::<ObjectIdentifier>()
+                        .ok()
+                        .map(|oid| oid_name(&oid));
+                }
+                ID_MESSAGE_DIGEST => {
+                    signer.message_digest = value
+                        .decode_as::<OctetString>()
+                        .ok()
+                        .map(|digest| to_hex(digest.as_bytes()));
+                }
+                _ => {}
+            }
+        }
+
+        signer
+    }
+}
+
+impl fmt::Display for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (
+            &self.issuer,
+            &self.serial_number,
+            &self.subject_key_identifier,
+        ) {
+            (Some(issuer), Some(serial_number), _) => write!(
+                f,
+                "Issuer                  : {}\n            \
+                Serial Number           : {}",
+                issuer, serial_number
+            )?,
+            (_, _, Some(ski)) => write!(f, "Subject Key Identifier  : {}", ski)?,
+            _ => write!(f, "Signer Identifier       : N/A")?,
+        }
+        write!(
+            f,
+            "\n            \
+            Digest Algorithm        : {}\n            \
+            Signature Algorithm     : {}\n            \
+            Signing Time            : {}\n            \
+            Content Type            : {}\n            \
+            Certificate         (CN): {}",
+            self.digest_algorithm,
+            self.signature_algorithm,
+            self.signing_time
+                .as_ref()
+                .map_or("N/A".to_owned(), |time| time.to_string()),
+            self.content_type.as_deref().unwrap_or("N/A"),
+            self.certificate
+                .as_ref()
+                .map_or("N/A", |cert| cert.common_name.as_str()),
+        )
+    }
+}
+
 #[derive(Default, Serialize)]
 /// Represents a signature found in an [`XPI`](`crate::XPI`).
 pub struct Signature {
     present: bool,
-    pub algorithm: Option<String>,
+    pub algorithm: Option<Algorithm>,
     pub certificates: Vec<CertificateInfo>,
+    /// The signers of a PKCS#7 signature. This is always empty for COSE signatures.
+    pub signers: Vec<Signer>,
+    /// The deviations from RFC 8152 found in a COSE signature. This is always empty for PKCS#7
+    /// signatures.
+    pub quirks: Vec<CoseQuirk>,
 }
 
 impl Signature {
@@ -195,13 +372,17 @@ impl Signature {
     }
 
     pub fn env(&self) -> Environment {
-        if self.exists() {
-            self.certificates[0].env()
-        } else {
-            Environment::Unknown
+        match self.certificates.first() {
+            Some(cert) if self.exists() => cert.env(),
+            _ => Environment::Unknown,
         }
     }
 
+    /// Returns the end-entity certificate, i.e. the certificate of the add-on itself.
+    pub fn end_entity(&self) -> Option<&CertificateInfo> {
+        self.certificates.last()
+    }
+
     pub fn kind(&self) -> SignatureKind {
         if self
             .certificates
@@ -228,12 +409,26 @@ impl fmt::Display for Signature {
             "   └── {} / {} / {} / {}\n   └── Certificates:",
             if self.present { "PRESENT" } else { "ABSENT" },
             self.env(),
-            self.algorithm.as_deref().unwrap_or("N/A"),
+            self.algorithm
+                .as_ref()
+                .map_or("N/A".to_owned(), |algorithm| algorithm.to_string()),
             self.kind(),
         )?;
         for cert in &self.certificates {
             write!(f, "\n        └── {}", cert)?;
         }
+        if !self.signers.is_empty() {
+            write!(f, "\n   └── Signers:")?;
+        }
+        for signer in &self.signers {
+            write!(f, "\n        └── {}", signer)?;
+        }
+        if !self.quirks.is_empty() {
+            write!(f, "\n   └── Quirks:")?;
+        }
+        for quirk in &self.quirks {
+            write!(f, "\n        └── {}", quirk)?;
+        }
         Ok(())
     }
 }
@@ -242,35 +437,58 @@ impl From<CoseSign> for Signature {
     fn from(cs: CoseSign) -> Self {
         Signature {
             present: true,
-            algorithm: Some(cs.algorithm),
+            algorithm: Some(
+                cs.algorithm
+                    .map_or(Algorithm::Unknown, Algorithm::Signature),
+            ),
             certificates: cs
                 .certificates
                 .into_iter()
                 .map(|c: Certificate| (&c).into())
                 .collect(),
+            signers: vec![],
+            quirks: cs.quirks,
         }
     }
 }
 
 impl From<SignedData> for Signature {
     fn from(data: SignedData) -> Self {
-        // TODO: avoid `unwrap()`
-        let digest_algorithm = match data.signer_infos.0.get(0).unwrap().digest_alg.oid {
-            ID_SHA_1 => "SHA-1",
-            ID_SHA_256 => "SHA-256",
-            _ => "unknown",
-        };
+        let certificates: Vec<&Certificate> = data
+            .certificates
+            .iter()
+            .flat_map(|certs| certs.0.iter())
+            .filter_map(|choice| match choice {
+                CertificateChoices::Certificate(cert) => Some(cert),
+                _ => None,
+            })
+            .collect();
+        let signers: Vec<Signer> = data
+            .signer_infos
+            .0
+            .iter()
+            .map(|info| Signer::new(info, &certificates))
+            .collect();
+
+        // The certificates are sorted by their DER encoding (`SET OF`), so the chain is rebuilt
+        // from the certificate of the first signer.
+        let end_entity = data
+            .signer_infos
+            .0
+            .get(0)
+            .and_then(|info| find_certificate(info, &certificates));
 
         Signature {
             present: true,
-            algorithm: Some(digest_algorithm.to_string()),
-            certificates: data
-                .certificates
-                .map_or(vec![], |certs| certs.0.into_vec())
-                .iter()
-                .rev()
-                .flat_map(|choice| choice.try_into())
+            algorithm: signers
+                .first()
+                .map(|signer| Algorithm::Digest(signer.digest_algorithm.clone())),
+            certificates: order_chain(&certificates, end_entity)
+                .into_iter()
+                .map(|cert| cert.into())
                 .collect(),
+            signers,
+            quirks: vec![],
         }
     }
 }
@@ -282,6 +500,8 @@ pub struct Signatures {
     pub pkcs7: Signature,
     /// A COSEish signature.
     pub cose: Signature,
+    /// The disagreements between the PKCS#7 and COSE signatures, when both are present.
+    pub mismatches: Vec<SignatureMismatch>,
 }
 
 impl Signatures {
@@ -291,16 +511,21 @@ impl Signatures {
     }
 
     pub(crate) fn parse<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> Signatures {
+        let pkcs7 = Signatures::parse_pkcs7(archive);
+        let cose = Signatures::parse_cose(archive);
+        let mismatches = check_consistency(archive, &pkcs7, &cose);
+
         Signatures {
-            pkcs7: Signatures::parse_pkcs7(archive),
-            cose: Signatures::parse_cose(archive),
+            pkcs7,
+            cose,
+            mismatches,
         }
     }
 
     fn parse_pkcs7<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> Signature {
-        let has_pkcs7_manifest = archive.by_name("META-INF/manifest.mf").is_ok();
-        let has_pkcs7_mozilla = archive.by_name("META-INF/mozilla.sf").is_ok();
-        let maybe_sig_file = archive.by_name("META-INF/mozilla.rsa");
+        let has_pkcs7_manifest = archive.by_name(PKCS7_MANIFEST_FILE).is_ok();
+        let has_pkcs7_mozilla = archive.by_name(PKCS7_SF_FILE).is_ok();
+        let maybe_sig_file = archive.by_name(PKCS7_SIGNATURE_FILE);
         let has_pkcs7 = has_pkcs7_manifest && has_pkcs7_mozilla && maybe_sig_file.is_ok();
 
         if let Ok(mut sig_file) = maybe_sig_file {
@@ -322,8 +547,8 @@ impl Signatures {
     }
 
     fn parse_cose<R: io::Read + io::Seek>(archive: &mut ZipArchive<R>) -> Signature {
-        let has_cose_manifest = archive.by_name("META-INF/cose.manifest").is_ok();
-        let maybe_sig_file = archive.by_name("META-INF/cose.sig");
+        let has_cose_manifest = archive.by_name(COSE_MANIFEST_FILE).is_ok();
+        let maybe_sig_file = archive.by_name(COSE_SIGNATURE_FILE);
         let has_cose = has_cose_manifest && maybe_sig_file.is_ok();
 
         if let Ok(mut sig_file) = maybe_sig_file {
@@ -348,8 +573,80 @@ impl fmt::Display for Signatures {
             f,
             "SIGNATURES:\n  PKCS7:\n{}\n  COSE:\n{}",
             self.pkcs7, self.cose
-        )
+        )?;
+        if self.pkcs7.exists() && self.cose.exists() {
+            write!(f, "\n  CONSISTENCY:")?;
+            if self.mismatches.is_empty() {
+                write!(f, "\n   └── OK")?;
+            }
+            for mismatch in &self.mismatches {
+                write!(f, "\n   └── {}", mismatch)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of an OID when it is known, or its dotted representation otherwise.
+pub(crate) fn oid_name(oid: &ObjectIdentifier) -> String {
+    DB.by_oid(oid)
+        .map_or_else(|| oid.to_string(), |name| name.to_owned())
+}
+
+/// Returns the certificate of a signer, if it is embedded in the signature.
+fn find_certificate<'a>(
+    info: &SignerInfo,
+    certificates: &[&'a Certificate],
+) -> Option<&'a Certificate> {
+    certificates
+        .iter()
+        .find(|cert| match &info.sid {
+            SignerIdentifier::IssuerAndSerialNumber(id) => {
+                cert.tbs_certificate.issuer == id.issuer
+                    && cert.tbs_certificate.serial_number == id.serial_number
+            }
+            SignerIdentifier::SubjectKeyIdentifier(id) => {
+                subject_key_identifier(cert).as_ref() == Some(id)
+            }
+        })
+        .copied()
+}
+
+/// Orders the certificates from the root to the end-entity certificate, following the issuers of
+/// the end-entity certificate. The certificates that are not part of its chain come first, in
+/// reverse order.
+fn order_chain<'a>(
+    certificates: &[&'a Certificate],
+    end_entity: Option<&'a Certificate>,
+) -> Vec<&'a Certificate> {
+    let mut chain: Vec<&Certificate> = vec![];
+    let mut current = end_entity;
+    while let Some(cert) = current.filter(|cert| !chain.contains(cert)) {
+        chain.push(cert);
+        current = certificates
+            .iter()
+            .find(|issuer| issuer.tbs_certificate.subject == cert.tbs_certificate.issuer)
+            .copied();
     }
+
+    let mut ordered: Vec<&Certificate> = certificates
+        .iter()
+        .rev()
+        .filter(|cert| !chain.contains(cert))
+        .copied()
+        .collect();
+    ordered.extend(chain.into_iter().rev());
+    ordered
+}
+
+/// Returns the subject key identifier extension of a certificate, if any.
+fn subject_key_identifier(cert: &Certificate) -> Option<SubjectKeyIdentifier> {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|ext| ext.extn_id == ID_CE_SUBJECT_KEY_IDENTIFIER)
+        .and_then(|ext| SubjectKeyIdentifier::from_der(ext.extn_value.as_bytes()).ok())
 }
 
 fn atv_to_string(atv: &AttributeTypeAndValue) -> String {
@@ -0,0 +1,405 @@
+use super::{signatures::is_signature_file, Error};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cms::{
+    cert::{
+        x509::{
+            attr::Attribute,
+            der::{
+                self,
+                asn1::{OctetString, SetOfVec, UtcTime},
+                Any, Encode,
+            },
+            spki::AlgorithmIdentifierOwned,
+            time::Time,
+            Certificate,
+        },
+        CertificateChoices, IssuerAndSerialNumber,
+    },
+    content_info::{CmsVersion, ContentInfo},
+    signed_data::{
+        CertificateSet, EncapsulatedContentInfo, SignedData, SignerIdentifier, SignerInfo,
+        SignerInfos,
+    },
+};
+use const_oid::db::{
+    rfc5911::{ID_CONTENT_TYPE, ID_DATA, ID_MESSAGE_DIGEST, ID_SIGNED_DATA, ID_SIGNING_TIME},
+    rfc5912::{ECDSA_WITH_SHA_256, ID_SHA_256},
+};
+use minicbor::{data::Tag, encode, Encoder};
+use p256::{
+    ecdsa::{signature::Signer, DerSignature, Signature as EcdsaSignature, SigningKey},
+    pkcs8::DecodePrivateKey,
+};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{
+    convert::Infallible,
+    error, fmt,
+    io::{self, Cursor, Read, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zip::{result::ZipError, write::FileOptions, ZipArchive, ZipWriter};
+
+const COSE_SIGN_TAG: u64 = 98;
+const COSE_ALG: u8 = 1;
+const COSE_KID: u8 = 4;
+/// The COSE identifier of ECDSA with SHA-256 (`ES256`).
+const COSE_ES256: i64 = -7;
+/// The maximum length of a line in a JAR manifest, in bytes.
+const MANIFEST_LINE_LENGTH: usize = 72;
+
+#[derive(Debug)]
+/// Represents the errors that can occur when signing an XPI file.
+pub enum SigningError {
+    /// The XPI file could not be read or written.
+    Xpi(Error),
+    /// The private key is not a PEM-encoded PKCS#8 P-256 key.
+    InvalidKey,
+    /// The certificate chain is empty or is not made of PEM-encoded certificates.
+    InvalidCertificates,
+    /// The private key does not match the end-entity certificate.
+    KeyMismatch,
+    /// A signature could not be encoded.
+    Encoding(der::Error),
+    /// The COSE signature could not be encoded.
+    CoseEncoding(encode::Error<Infallible>),
+}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SigningError::Xpi(_) => "failed to read or write XPI file",
+                SigningError::InvalidKey => "invalid private key, expected a PKCS#8 P-256 key",
+                SigningError::InvalidCertificates => "invalid certificate chain",
+                SigningError::KeyMismatch =>
+                    "the private key does not match the end-entity certificate",
+                SigningError::Encoding(_) => "failed to encode signature",
+                SigningError::CoseEncoding(_) => "failed to encode COSE signature",
+            }
+        )
+    }
+}
+
+impl error::Error for SigningError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SigningError::Xpi(err) => Some(err),
+            SigningError::Encoding(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SigningError {
+    fn from(err: io::Error) -> Self {
+        SigningError::Xpi(err.into())
+    }
+}
+
+impl From<ZipError> for SigningError {
+    fn from(err: ZipError) -> Self {
+        SigningError::Xpi(err.into())
+    }
+}
+
+impl From<der::Error> for SigningError {
+    fn from(err: der::Error) -> Self {
+        SigningError::Encoding(err)
+    }
+}
+
+impl From<encode::Error<Infallible>> for SigningError {
+    fn from(err: encode::Error<Infallible>) -> Self {
+        SigningError::CoseEncoding(err)
+    }
+}
+
+/// Represents the private key and the certificate chain used to sign XPI files offline, e.g. to
+/// generate test fixtures without the AMO signing service.
+///
+/// Only P-256 keys are supported: the PKCS#7 signature uses ECDSA with SHA-256 and the COSE
+/// signature uses `ES256`.
+pub struct SigningCredentials {
+    key: SigningKey,
+    /// The end-entity certificate followed by the intermediate certificates.
+    certificates: Vec<Certificate>,
+}
+
+impl SigningCredentials {
+    /// Loads a PEM-encoded PKCS#8 private key and a PEM-encoded certificate chain, which starts
+    /// with the end-entity certificate (i.e. the certificate of the key). The root certificate
+    /// should not be part of the chain.
+    pub fn from_pem(key: &str, chain: &str) -> Result<SigningCredentials, SigningError> {
+        let key = SigningKey::from_pkcs8_pem(key).map_err(|_| SigningError::InvalidKey)?;
+        // `load_pem_chain()` panics on empty inputs.
+        if chain.trim().is_empty() {
+            return Err(SigningError::InvalidCertificates);
+        }
+        let certificates = Certificate::load_pem_chain(chain.as_bytes())
+            .map_err(|_| SigningError::InvalidCertificates)?;
+
+        SigningCredentials::new(key, certificates)
+    }
+
+    pub(crate) fn new(
+        key: SigningKey,
+        certificates: Vec<Certificate>,
+    ) -> Result<SigningCredentials, SigningError> {
+        let end_entity = certificates
+            .first()
+            .ok_or(SigningError::InvalidCertificates)?;
+        let public_key = key.verifying_key().to_encoded_point(false);
+        if end_entity
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes()
+            != public_key.as_bytes()
+        {
+            return Err(SigningError::KeyMismatch);
+        }
+
+        Ok(SigningCredentials { key, certificates })
+    }
+
+    /// Signs an XPI file and returns the signed XPI file. The existing signature files are
+    /// replaced by new PKCS#7 (`META-INF/manifest.mf`, `META-INF/mozilla.sf` and
+    /// `META-INF/mozilla.rsa`) and COSE (`META-INF/cose.manifest` and `META-INF/cose.sig`)
+    /// signatures, laid out like the ones produced by AMO.
+    pub fn sign(&self, bytes: &[u8]) -> Result<Vec<u8>, SigningError> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        let mut entries = vec![];
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            let name = file.name().to_owned();
+            if is_signature_file(&name) {
+                continue;
+            }
+            if !file.is_dir() {
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)?;
+                entries.push(ManifestEntry::new(name, &content));
+            }
+            // The file is copied as is, without decompressing it again.
+            drop(file);
+            writer.raw_copy_file(archive.by_index(index)?)?;
+        }
+
+        // The COSE signature is computed first because its files are covered by the PKCS#7
+        // signature.
+        let cose_manifest = write_manifest(&entries);
+        let cose_signature = self.sign_cose(&cose_manifest)?;
+        entries.push(ManifestEntry::new(
+            "META-INF/cose.manifest".to_owned(),
+            &cose_manifest,
+        ));
+        entries.push(ManifestEntry::new(
+            "META-INF/cose.sig".to_owned(),
+            &cose_signature,
+        ));
+
+        let manifest = write_manifest(&entries);
+        let signature_file = write_signature_file(&manifest);
+        let pkcs7_signature = self.sign_pkcs7(&signature_file)?;
+
+        for (name, content) in [
+            ("META-INF/cose.manifest", &cose_manifest),
+            ("META-INF/cose.sig", &cose_signature),
+            ("META-INF/manifest.mf", &manifest),
+            ("META-INF/mozilla.sf", &signature_file),
+            ("META-INF/mozilla.rsa", &pkcs7_signature),
+        ] {
+            writer.start_file(name, FileOptions::default())?;
+            writer.write_all(content)?;
+        }
+
+        Ok(writer.finish()?.into_inner())
+    }
+
+    /// Builds a `COSE_Sign` structure for a detached payload (the COSE manifest). Like AMO, the
+    /// intermediate certificates are stored in the `kid` header of the structure and the
+    /// end-entity certificate in the `kid` header of the signer.
+    fn sign_cose(&self, payload: &[u8]) -> Result<Vec<u8>, SigningError> {
+        let mut protected = Encoder::new(Vec::new());
+        protected
+            .map(1)?
+            .u8(COSE_KID)?
+            .array(self.certificates.len() as u64 - 1)?;
+        for cert in &self.certificates[1..] {
+            protected.bytes(&cert.to_der()?)?;
+        }
+        let protected = protected.into_writer();
+
+        let mut signer_protected = Encoder::new(Vec::new());
+        signer_protected
+            .map(2)?
+            .u8(COSE_ALG)?
+            .i64(COSE_ES256)?
+            .u8(COSE_KID)?
+            .bytes(&self.certificates[0].to_der()?)?;
+        let signer_protected = signer_protected.into_writer();
+
+        // Sig_structure = [ context, body_protected, sign_protected, external_aad, payload ]
+        let mut to_be_signed = Encoder::new(Vec::new());
+        to_be_signed
+            .array(5)?
+            .str("Signature")?
+            .bytes(&protected)?
+            .bytes(&signer_protected)?
+            .bytes(&[])?
+            .bytes(payload)?;
+        let signature: EcdsaSignature = self.key.sign(to_be_signed.writer());
+
+        let mut encoder = Encoder::new(Vec::new());
+        encoder
+            .tag(Tag::new(COSE_SIGN_TAG))?
+            .array(4)?
+            .bytes(&protected)?
+            .map(0)?
+            .null()?
+            .array(1)?
+            .array(3)?
+            .bytes(&signer_protected)?
+            .map(0)?
+            .bytes(&signature.to_bytes())?;
+
+        Ok(encoder.into_writer())
+    }
+
+    /// Builds a detached PKCS#7 `SignedData` structure for the signature file, with the
+    /// `contentType`, `signingTime` and `messageDigest` signed attributes.
+    fn sign_pkcs7(&self, content: &[u8]) -> Result<Vec<u8>, SigningError> {
+        let end_entity = &self.certificates[0];
+        let sha256 = AlgorithmIdentifierOwned {
+            oid: ID_SHA_256,
+            parameters: None,
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let signed_attrs = SetOfVec::try_from(vec![
+            attribute(ID_CONTENT_TYPE, Any::encode_from(&ID_DATA)?)?,
+            attribute(
+                ID_SIGNING_TIME,
+                Any::encode_from(&Time::UtcTime(UtcTime::from_unix_duration(now)?))?,
+            )?,
+            attribute(
+                ID_MESSAGE_DIGEST,
+                Any::encode_from(&OctetString::new(Sha256::digest(content).to_vec())?)?,
+            )?,
+        ])?;
+        // The signature covers the DER encoding of the signed attributes as a `SET OF`.
+        let signature: DerSignature = self.key.sign(&signed_attrs.to_der()?);
+
+        let signer_info = SignerInfo {
+            version: CmsVersion::V1,
+            sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+                issuer: end_entity.tbs_certificate.issuer.clone(),
+                serial_number: end_entity.tbs_certificate.serial_number.clone(),
+            }),
+            digest_alg: sha256.clone(),
+            signed_attrs: Some(signed_attrs),
+            signature_algorithm: AlgorithmIdentifierOwned {
+                oid: ECDSA_WITH_SHA_256,
+                parameters: None,
+            },
+            signature: OctetString::new(signature.as_bytes())?,
+            unsigned_attrs: None,
+        };
+
+        let data = SignedData {
+            version: CmsVersion::V1,
+            digest_algorithms: SetOfVec::try_from(vec![sha256])?,
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: ID_DATA,
+                econtent: None,
+            },
+            certificates: Some(CertificateSet(SetOfVec::try_from(
+                self.certificates
+                    .iter()
+                    .cloned()
+                    .map(CertificateChoices::Certificate)
+                    .collect::<Vec<_>>(),
+            )?)),
+            crls: None,
+            signer_infos: SignerInfos(SetOfVec::try_from(vec![signer_info])?),
+        };
+
+        Ok(ContentInfo {
+            content_type: ID_SIGNED_DATA,
+            content: Any::encode_from(&data)?,
+        }
+        .to_der()?)
+    }
+}
+
+/// Represents a file listed in a JAR manifest, with its digests (base64-encoded).
+struct ManifestEntry {
+    name: String,
+    sha1: String,
+    sha256: String,
+}
+
+impl ManifestEntry {
+    fn new(name: String, content: &[u8]) -> Self {
+        ManifestEntry {
+            name,
+            sha1: STANDARD.encode(Sha1::digest(content)),
+            sha256: STANDARD.encode(Sha256::digest(content)),
+        }
+    }
+}
+
+fn write_manifest(entries: &[ManifestEntry]) -> Vec<u8> {
+    let mut manifest = "Manifest-Version: 1.0\n\n".to_owned();
+    for entry in entries {
+        manifest.push_str(&wrap_line(&format!("Name: {}", entry.name)));
+        manifest.push_str(&format!(
+            "Digest-Algorithms: SHA1 SHA256\nSHA1-Digest: {}\nSHA256-Digest: {}\n\n",
+            entry.sha1, entry.sha256
+        ));
+    }
+
+    manifest.into_bytes()
+}
+
+/// Writes the signature file (`META-INF/mozilla.sf`), which contains the digests of the manifest.
+fn write_signature_file(manifest: &[u8]) -> Vec<u8> {
+    format!(
+        "Signature-Version: 1.0\nSHA1-Digest-Manifest: {}\nSHA256-Digest-Manifest: {}\n\n",
+        STANDARD.encode(Sha1::digest(manifest)),
+        STANDARD.encode(Sha256::digest(manifest))
+    )
+    .into_bytes()
+}
+
+/// Splits a manifest line into lines of at most 72 bytes, the continuation lines starting with a
+/// space.
+fn wrap_line(line: &str) -> String {
+    let mut wrapped = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MANIFEST_LINE_LENGTH {
+            wrapped.push_str("\n ");
+            length = 1;
+        }
+        wrapped.push(c);
+        length += c.len_utf8();
+    }
+    wrapped.push('\n');
+
+    wrapped
+}
+
+fn attribute(oid: const_oid::ObjectIdentifier, value: Any) -> Result<Attribute, der::Error> {
+    Ok(Attribute {
+        oid,
+        values: SetOfVec::try_from(vec![value])?,
+    })
+}
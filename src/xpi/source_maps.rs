@@ -0,0 +1,244 @@
+use super::references::normalize;
+use serde::Serialize;
+use std::fmt;
+
+// The markers left by the bundlers in their output.
+const WEBPACK_MARKERS: &[&str] = &[
+    "__webpack_require__",
+    "webpackBootstrap",
+    "webpackJsonp",
+    "webpackChunk",
+    // The minified webpack 4 runtime.
+    "l:!1,exports:{}",
+];
+const ROLLUP_MARKERS: &[&str] = &[
+    "getDefaultExportFromCjs",
+    "createCommonjsModule",
+    "commonjsGlobal",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the tools used to bundle JavaScript files.
+pub enum Bundler {
+    Webpack,
+    Rollup,
+}
+
+impl fmt::Display for Bundler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Bundler::Webpack => "WEBPACK",
+                Bundler::Rollup => "ROLLUP",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a file that has been generated by a bundler.
+pub struct Bundle {
+    /// The path of the file in the archive.
+    pub path: String,
+    pub bundler: Bundler,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the location of the source map referenced by a `sourceMappingURL` comment.
+pub enum SourceMapLocation {
+    /// The source map is stored in the XPI.
+    Found,
+    /// The source map should be stored in the XPI but it is not.
+    Missing,
+    /// The source map is loaded from a remote server.
+    Remote,
+    /// The source map is inlined in the file (`data:` URL).
+    Inline,
+}
+
+impl fmt::Display for SourceMapLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SourceMapLocation::Found => "FOUND",
+                SourceMapLocation::Missing => "MISSING",
+                SourceMapLocation::Remote => "REMOTE",
+                SourceMapLocation::Inline => "INLINE",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a `sourceMappingURL` comment found in a JavaScript or CSS file.
+pub struct SourceMappingUrl {
+    /// The path of the file containing the comment.
+    pub path: String,
+    /// The line number, starting at 1.
+    pub line: usize,
+    /// The URL, as written in the comment. Inline source maps are truncated.
+    pub url: String,
+    pub location: SourceMapLocation,
+    /// The path of the source map in the archive, for relative URLs.
+    pub resolved_path: Option<String>,
+}
+
+impl fmt::Display for SourceMappingUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ({})",
+            self.path,
+            self.line,
+            self.resolved_path.as_deref().unwrap_or(&self.url),
+            self.location
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a source map (`.map` file) stored in the XPI.
+pub struct SourceMap {
+    /// The path of the source map in the archive.
+    pub path: String,
+    /// The generated file described by the source map (`file` property), if any.
+    pub file: Option<String>,
+    /// The original sources listed in the source map, prefixed with the `sourceRoot` property.
+    pub sources: Vec<String>,
+    /// Whether the source map embeds the content of the original sources (`sourcesContent`).
+    pub has_sources_content: bool,
+}
+
+impl SourceMap {
+    /// Parses a source map, `None` is returned when the content is not a valid source map.
+    pub fn parse(path: &str, content: &[u8]) -> Option<SourceMap> {
+        let data: serde_json::Value = serde_json::from_slice(content).ok()?;
+        let sources = data.get("sources")?.as_array()?;
+        let source_root = data
+            .get("sourceRoot")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+
+        Some(SourceMap {
+            path: path.to_owned(),
+            file: data
+                .get("file")
+                .and_then(|value| value.as_str())
+                .map(|s| s.to_owned()),
+            sources: sources
+                .iter()
+                .filter_map(|source| source.as_str())
+                .map(|source| format!("{}{}", source_root, source))
+                .collect(),
+            has_sources_content: data
+                .get("sourcesContent")
+                .and_then(|value| value.as_array())
+                .is_some_and(|contents| !contents.is_empty()),
+        })
+    }
+}
+
+impl fmt::Display for SourceMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} source(s){})",
+            self.path,
+            self.sources.len(),
+            if self.has_sources_content {
+                ", with content"
+            } else {
+                ""
+            }
+        )?;
+        for source in &self.sources {
+            write!(f, "\n   └── {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the file at `path` is a source map, based on its extension.
+pub(crate) fn is_source_map(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".map")
+}
+
+/// Returns the bundler that has generated `text`, if any.
+pub(crate) fn find_bundler(text: &str) -> Option<Bundler> {
+    if WEBPACK_MARKERS.iter().any(|marker| text.contains(marker)) {
+        Some(Bundler::Webpack)
+    } else if ROLLUP_MARKERS.iter().any(|marker| text.contains(marker)) {
+        Some(Bundler::Rollup)
+    } else {
+        None
+    }
+}
+
+/// Returns the `sourceMappingURL` comments found in `text`. `exists` tells whether a file exists
+/// in the archive, in order to resolve the relative URLs.
+pub(crate) fn find_source_mapping_urls<F>(
+    path: &str,
+    text: &str,
+    exists: F,
+) -> Vec<SourceMappingUrl>
+where
+    F: Fn(&str) -> bool,
+{
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.trim();
+            let url = [
+                "//# sourceMappingURL=",
+                "//@ sourceMappingURL=",
+                "/*# sourceMappingURL=",
+            ]
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))?;
+            let url = url.trim_end_matches("*/").trim();
+
+            let (location, resolved_path) = if url.starts_with("data:") {
+                (SourceMapLocation::Inline, None)
+            } else if url.contains("://") || url.starts_with("//") {
+                (SourceMapLocation::Remote, None)
+            } else {
+                let resolved = resolve_path(path, url);
+                if exists(&resolved) {
+                    (SourceMapLocation::Found, Some(resolved))
+                } else {
+                    (SourceMapLocation::Missing, Some(resolved))
+                }
+            };
+
+            Some(SourceMappingUrl {
+                path: path.to_owned(),
+                line: index + 1,
+                url: match url.char_indices().nth(64) {
+                    Some((end, _)) if location == SourceMapLocation::Inline => {
+                        format!("{}...", &url[..end])
+                    }
+                    _ => url.to_owned(),
+                },
+                location,
+                resolved_path,
+            })
+        })
+        .collect()
+}
+
+/// Resolves `url` relatively to the directory of `path`, e.g. `js/app.js` and `app.js.map` gives
+/// `js/app.js.map`.
+fn resolve_path(path: &str, url: &str) -> String {
+    let directory = match path.rsplit_once('/') {
+        Some((directory, _)) if !url.starts_with('/') => directory,
+        _ => "",
+    };
+
+    normalize(&format!("{}/{}", directory, url)).unwrap_or_default()
+}
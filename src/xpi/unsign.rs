@@ -0,0 +1,50 @@
+use super::{signatures::is_signature_file, Error};
+use serde::Serialize;
+use std::{fmt, io::Cursor};
+use zip::{ZipArchive, ZipWriter};
+
+#[derive(Serialize)]
+/// Represents an XPI file whose signature files have been removed, see [`unsign`].
+pub struct UnsignedXPI {
+    /// The content of the rewritten XPI file.
+    #[serde(skip)]
+    pub bytes: Vec<u8>,
+    /// The names of the signature files that have been removed, in archive order.
+    pub removed_files: Vec<String>,
+}
+
+impl fmt::Display for UnsignedXPI {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "REMOVED FILES:")?;
+        if self.removed_files.is_empty() {
+            write!(f, "\n  NONE")?;
+        }
+        for name in &self.removed_files {
+            write!(f, "\n  {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites an XPI file without its PKCS#7 (`META-INF/manifest.mf`, `META-INF/mozilla.sf` and
+/// `META-INF/mozilla.rsa`) and COSE (`META-INF/cose.manifest` and `META-INF/cose.sig`) signature
+/// files. The other entries are copied as is, without decompressing them.
+pub fn unsign(bytes: &[u8]) -> Result<UnsignedXPI, Error> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    let mut removed_files = vec![];
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index)?;
+        if is_signature_file(file.name()) {
+            removed_files.push(file.name().to_owned());
+            continue;
+        }
+        writer.raw_copy_file(file)?;
+    }
+
+    Ok(UnsignedXPI {
+        bytes: writer.finish()?.into_inner(),
+        removed_files,
+    })
+}
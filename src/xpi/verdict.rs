@@ -0,0 +1,391 @@
+use super::{
+    signatures::{COSE_FILES, COSE_MANIFEST_FILE, PKCS7_FILES, PKCS7_MANIFEST_FILE, PKCS7_SF_FILE},
+    CertificateInfo, Environment, SignatureKind, Signatures,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{fmt, io, io::Read};
+use zip::ZipArchive;
+
+/// The root certificates used to sign add-ons, with the environment they belong to.
+const KNOWN_ROOTS: &[(&str, Environment)] = &[
+    ("root-ca-production-amo", Environment::Production),
+    ("cas-root-ca-staging", Environment::Staging),
+    ("test.addons.signing.root.ca", Environment::Staging),
+    ("dev.amo.root.ca", Environment::Development),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Represents the overall state of the signature of an XPI file.
+///
+/// The root certificate is only identified by the issuer name of the certificate chain, which
+/// anyone can forge: the `Claims*` statuses tell which AMO root the chain names, not that the
+/// XPI file has been signed by AMO.
+pub enum VerdictStatus {
+    /// The XPI file does not contain any signature.
+    Unsigned,
+    /// The XPI file contains signature files but its content does not match them, or the
+    /// signature files are incomplete.
+    Tampered,
+    /// The certificate chain does not name a known root certificate.
+    SignedUntrustedRoot,
+    /// The certificate chain names the root certificate of the development environment.
+    ClaimsDevelopmentRoot,
+    /// The certificate chain names the root certificate of the staging environment.
+    ClaimsStagingRoot,
+    /// The certificate chain names the root certificate of the production environment.
+    ClaimsProductionRoot,
+}
+
+impl fmt::Display for VerdictStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                VerdictStatus::Unsigned => "UNSIGNED",
+                VerdictStatus::Tampered => "TAMPERED",
+                VerdictStatus::SignedUntrustedRoot => "SIGNED (UNTRUSTED ROOT)",
+                VerdictStatus::ClaimsDevelopmentRoot => "CLAIMS DEVELOPMENT ROOT (NOT VERIFIED)",
+                VerdictStatus::ClaimsStagingRoot => "CLAIMS STAGING ROOT (NOT VERIFIED)",
+                VerdictStatus::ClaimsProductionRoot => "CLAIMS PRODUCTION ROOT (NOT VERIFIED)",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+/// Represents a summary of the signature of an XPI file, see
+/// [`XPI::verdict()`](`crate::XPI::verdict`).
+///
+/// The content of the XPI file is checked against the signed manifests, and the issuer names of
+/// the certificate chain against the known root certificates. Like Firefox, the COSE signature is
+/// evaluated when there is one. Nothing is cryptographically verified: neither the signatures
+/// nor the certificate chain.
+pub struct Verdict {
+    pub status: VerdictStatus,
+    /// The common name of the root certificate named by the certificate chain, when the XPI file
+    /// is signed.
+    pub root: Option<String>,
+    /// Whether the XPI file has been signed as a privileged add-on.
+    pub privileged: bool,
+    /// Whether the XPI file has been signed as a system add-on.
+    pub system: bool,
+    /// Whether the XPI file has been signed as an enterprise add-on.
+    pub enterprise: bool,
+    /// Whether the XPI file has been signed as a recommended add-on.
+    pub recommended: bool,
+    /// The reasons explaining the status and the flags.
+    pub reasons: Vec<String>,
+}
+
+impl Verdict {
+    /// Builds the verdict of an XPI file. `integrity_issues` are the problems returned by
+    /// [`check_integrity`].
+    pub(crate) fn new(
+        signatures: &Signatures,
+        integrity_issues: Vec<String>,
+        enterprise: bool,
+        recommended: bool,
+    ) -> Verdict {
+        let mut verdict = Verdict {
+            status: VerdictStatus::Unsigned,
+            root: None,
+            privileged: false,
+            system: false,
+            enterprise: false,
+            recommended: false,
+            reasons: vec![],
+        };
+
+        if !signatures.has_signatures() && integrity_issues.is_empty() {
+            verdict.reasons.push("no signature found".to_owned());
+            return verdict;
+        }
+
+        let (pkcs7, cose) = (&signatures.pkcs7, &signatures.cose);
+        for (name, signature) in [("PKCS#7", pkcs7), ("COSE", cose)] {
+            if signature.exists() && signature.certificates.is_empty() {
+                verdict
+                    .reasons
+                    .push(format!("the {} signature cannot be parsed", name));
+            }
+        }
+        verdict.reasons.extend(integrity_issues);
+        if !verdict.reasons.is_empty() {
+            verdict.status = VerdictStatus::Tampered;
+            return verdict;
+        }
+
+        // Firefox verifies the COSE signature when there is one.
+        let (name, signature) = if cose.exists() {
+            ("COSE", cose)
+        } else {
+            ("PKCS#7", pkcs7)
+        };
+        if !pkcs7.exists() {
+            verdict.reasons.push(
+                "the PKCS#7 signature is missing, only the COSE signature is present".to_owned(),
+            );
+        }
+
+        let root = find_root(&signature.certificates);
+        verdict.status = match KNOWN_ROOTS.iter().find(|(known, _)| Some(*known) == root) {
+            Some((known, env)) => {
+                verdict.reasons.push(format!(
+                    "the {} certificate chain claims the {} root certificate ({}), which is not \
+                     cryptographically verified",
+                    name,
+                    env.to_string().to_lowercase(),
+                    known
+                ));
+                match env {
+                    Environment::Development => VerdictStatus::ClaimsDevelopmentRoot,
+                    Environment::Staging => VerdictStatus::ClaimsStagingRoot,
+                    _ => VerdictStatus::ClaimsProductionRoot,
+                }
+            }
+            None => {
+                verdict.reasons.push(format!(
+                    "the root certificate ({}) of the {} certificate chain is not trusted",
+                    root.unwrap_or("N/A"),
+                    name
+                ));
+                VerdictStatus::SignedUntrustedRoot
+            }
+        };
+        verdict.root = root.map(|root| root.to_owned());
+
+        if cose.exists() && pkcs7.exists() {
+            let pkcs7_root = find_root(&pkcs7.certificates);
+            if pkcs7_root != root {
+                verdict.reasons.push(format!(
+                    "the PKCS#7 certificate chain names another root certificate ({})",
+                    pkcs7_root.unwrap_or("N/A")
+                ));
+            }
+        }
+
+        match signature.kind() {
+            SignatureKind::Privileged => {
+                verdict.privileged = true;
+                verdict
+                    .reasons
+                    .push("signed as a privileged add-on".to_owned());
+            }
+            SignatureKind::System => {
+                verdict.system = true;
+                verdict.reasons.push("signed as a system add-on".to_owned());
+            }
+            SignatureKind::Regular => {}
+        }
+        if enterprise {
+            verdict.enterprise = true;
+            verdict
+                .reasons
+                .push("signed as an enterprise add-on".to_owned());
+        }
+        if recommended {
+            verdict.recommended = true;
+            verdict
+                .reasons
+                .push("signed with a recommendation state".to_owned());
+        }
+
+        verdict
+    }
+
+    /// Whether the XPI file is signed and its content matches the signature, regardless of the
+    /// root certificate. The signatures are not cryptographically verified.
+    pub fn is_signed(&self) -> bool {
+        !matches!(
+            self.status,
+            VerdictStatus::Unsigned | VerdictStatus::Tampered
+        )
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VERDICT:\n  {}", self.status)?;
+
+        let flags: Vec<&str> = [
+            (self.privileged, "PRIVILEGED"),
+            (self.system, "SYSTEM"),
+            (self.enterprise, "ENTERPRISE"),
+            (self.recommended, "RECOMMENDED"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, flag)| enabled.then_some(flag))
+        .collect();
+        if !flags.is_empty() {
+            write!(f, " [{}]", flags.join(", "))?;
+        }
+
+        for reason in &self.reasons {
+            write!(f, "\n   └── {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the content of the archive against the signed manifests (`META-INF/manifest.mf` for
+/// PKCS#7 and `META-INF/cose.manifest` for COSE) and returns the problems found.
+pub(crate) fn check_integrity<R: io::Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    signatures: &Signatures,
+) -> Vec<String> {
+    let mut issues = vec![];
+
+    for (name, files, signature) in [
+        ("PKCS#7", &PKCS7_FILES[..], &signatures.pkcs7),
+        ("COSE", &COSE_FILES[..], &signatures.cose),
+    ] {
+        if !signature.exists() && files.iter().any(|file| archive.by_name(file).is_ok()) {
+            issues.push(format!("the {} signature files are incomplete", name));
+        }
+    }
+
+    if signatures.pkcs7.exists() {
+        check_manifest(archive, PKCS7_MANIFEST_FILE, &PKCS7_FILES, &mut issues);
+
+        // The signature file contains the digest of the whole manifest.
+        let manifest = read_file(archive, PKCS7_MANIFEST_FILE).unwrap_or_default();
+        let signature_file = read_file(archive, PKCS7_SF_FILE).unwrap_or_default();
+        let main_section = parse_manifest(&signature_file).into_iter().next();
+        if !main_section
+            .is_some_and(|section| check_digests(&section, "-Digest-Manifest", &manifest))
+        {
+            issues.push(
+                "the digest of META-INF/manifest.mf does not match META-INF/mozilla.sf".to_owned(),
+            );
+        }
+    }
+
+    if signatures.cose.exists() {
+        let excluded: Vec<&str> = COSE_FILES.iter().chain(&PKCS7_FILES).copied().collect();
+        check_manifest(archive, COSE_MANIFEST_FILE, &excluded, &mut issues);
+    }
+
+    issues
+}
+
+/// Checks the files of the archive against the manifest stored at `path`. The `excluded` files
+/// are not expected to be listed in the manifest.
+fn check_manifest<R: io::Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &str,
+    excluded: &[&str],
+    issues: &mut Vec<String>,
+) {
+    let Some(content) = read_file(archive, path) else {
+        issues.push(format!("{} cannot be read", path));
+        return;
+    };
+
+    let mut listed = vec![];
+    for section in parse_manifest(&content) {
+        let Some((_, name)) = section.iter().find(|(key, _)| key == "Name") else {
+            continue;
+        };
+        listed.push(name.to_owned());
+
+        match read_file(archive, name) {
+            Some(file) if check_digests(&section, "-Digest", &file) => {}
+            Some(_) => issues.push(format!("{} does not match its digest in {}", name, path)),
+            None => issues.push(format!("{} is listed in {} but missing", name, path)),
+        }
+    }
+
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| name.to_owned())
+        .collect();
+    for name in names {
+        if !excluded.contains(&name.as_str()) && !listed.contains(&name) {
+            issues.push(format!("{} is not listed in {}", name, path));
+        }
+    }
+}
+
+/// Whether `content` matches the digests of a manifest section, i.e. the attributes ending with
+/// `suffix` (e.g. `SHA256-Digest`). At least one digest with a supported algorithm is required.
+fn check_digests(section: &[(String, String)], suffix: &str, content: &[u8]) -> bool {
+    let mut checked = false;
+
+    for (key, value) in section {
+        let Some(algorithm) = key.strip_suffix(suffix) else {
+            continue;
+        };
+        let digest = match algorithm.to_ascii_uppercase().as_str() {
+            "SHA1" => Sha1::digest(content).to_vec(),
+            "SHA256" => Sha256::digest(content).to_vec(),
+            "SHA384" => Sha384::digest(content).to_vec(),
+            "SHA512" => Sha512::digest(content).to_vec(),
+            // MD5 and other algorithms are ignored.
+            _ => continue,
+        };
+        if STANDARD.decode(value).ok() != Some(digest) {
+            return false;
+        }
+        checked = true;
+    }
+
+    checked
+}
+
+/// Parses a JAR manifest (or signature file) into sections of attributes. Long lines are split
+/// into continuation lines starting with a space.
+pub(crate) fn parse_manifest(content: &[u8]) -> Vec<Vec<(String, String)>> {
+    let content = String::from_utf8_lossy(content);
+    let mut sections = vec![];
+    let mut section: Vec<(String, String)> = vec![];
+
+    for line in content.lines() {
+        if line.is_empty() {
+            if !section.is_empty() {
+                sections.push(std::mem::take(&mut section));
+            }
+        } else if let Some(continuation) = line.strip_prefix(' ') {
+            if let Some((_, value)) = section.last_mut() {
+                value.push_str(continuation);
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            section.push((key.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+    if !section.is_empty() {
+        sections.push(section);
+    }
+
+    sections
+}
+
+/// Returns the common name of the root certificate of a chain, i.e. the issuer of the certificate
+/// that has not been issued by another certificate of the chain.
+fn find_root(certificates: &[CertificateInfo]) -> Option<&str> {
+    certificates
+        .iter()
+        .enumerate()
+        .find(|(index, cert)| {
+            !certificates.iter().enumerate().any(|(other_index, other)| {
+                other_index != *index && other.common_name == cert.issuer_common_name
+            })
+        })
+        .map(|(_, cert)| cert.issuer_common_name.as_str())
+}
+
+pub(crate) fn read_file<R: io::Read + io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Option<Vec<u8>> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).ok()?;
+
+    Some(content)
+}
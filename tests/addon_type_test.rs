@@ -0,0 +1,132 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{AddonType, Dictionary, XPI};
+
+#[test]
+fn test_extension() {
+    let xpi = XPI::from_bytes(include_bytes!("fixtures/amo_info-1.25.0.xpi")).unwrap();
+
+    assert_eq!(Some(AddonType::Extension), xpi.addon_type);
+}
+
+#[test]
+fn test_no_manifest() {
+    let xpi = XPI::from_bytes(&make_xpi(&[("index.js", b"")])).unwrap();
+
+    assert_eq!(None, xpi.addon_type);
+}
+
+#[test]
+fn test_theme() {
+    let manifest = br##"{
+      "manifest_version": 2,
+      "name": "A theme",
+      "version": "1.0",
+      "theme": {
+        "images": {
+          "theme_frame": "images/header.png",
+          "additional_backgrounds": ["images/bg1.png", "images/bg2.png"]
+        },
+        "colors": {
+          "frame": "#adb09f",
+          "tab_background_text": [0, 0, 0]
+        }
+      }
+    }"##;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    let Some(AddonType::Theme(details)) = xpi.addon_type else {
+        panic!("expect a theme");
+    };
+    assert_eq!("#adb09f", details.colors["frame"]);
+    assert_eq!("[0,0,0]", details.colors["tab_background_text"]);
+    assert_eq!(vec!["images/header.png"], details.images["theme_frame"]);
+    assert_eq!(
+        vec!["images/bg1.png", "images/bg2.png"],
+        details.images["additional_backgrounds"]
+    );
+}
+
+#[test]
+fn test_dictionary() {
+    let manifest = br##"{
+      "manifest_version": 2,
+      "name": "A dictionary",
+      "version": "1.0",
+      "dictionaries": {
+        "fr": "dictionaries/fr.dic",
+        "de": "dictionaries/de.dic"
+      }
+    }"##;
+    let xpi = XPI::from_bytes(&make_xpi(&[
+        ("manifest.json", manifest),
+        ("dictionaries/fr.dic", b""),
+        ("dictionaries/fr.aff", b""),
+        ("dictionaries/de.dic", b""),
+    ]))
+    .unwrap();
+
+    let Some(AddonType::Dictionary(details)) = xpi.addon_type else {
+        panic!("expect a dictionary");
+    };
+    assert!(details.dictionaries.contains(&Dictionary {
+        locale: "fr".to_owned(),
+        path: "dictionaries/fr.dic".to_owned(),
+        has_dic_file: true,
+        has_aff_file: true,
+    }));
+    assert!(details.dictionaries.contains(&Dictionary {
+        locale: "de".to_owned(),
+        path: "dictionaries/de.dic".to_owned(),
+        has_dic_file: true,
+        has_aff_file: false,
+    }));
+}
+
+#[test]
+fn test_language_pack() {
+    let manifest = br##"{
+      "manifest_version": 2,
+      "name": "Language: Francais",
+      "version": "125.0.20240401.1",
+      "langpack_id": "fr",
+      "languages": {
+        "fr": { "chrome_resources": {}, "version": "20240401" }
+      },
+      "browser_specific_settings": {
+        "gecko": {
+          "id": "langpack-fr@firefox.mozilla.org",
+          "strict_min_version": "125.0",
+          "strict_max_version": "125.*"
+        }
+      }
+    }"##;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    let Some(AddonType::LanguagePack(details)) = xpi.addon_type else {
+        panic!("expect a language pack");
+    };
+    assert_eq!("fr", details.langpack_id);
+    assert_eq!(vec!["fr"], details.languages);
+    assert_eq!(Some("125.0".to_owned()), details.strict_min_version);
+    assert_eq!(Some("125.*".to_owned()), details.strict_max_version);
+}
+
+#[test]
+fn test_site_permission() {
+    let manifest = br##"{
+      "manifest_version": 2,
+      "name": "Site permissions",
+      "version": "1.0",
+      "site_permissions": ["midi", "midi-sysex"],
+      "install_origins": ["https://example.com"]
+    }"##;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    let Some(AddonType::SitePermission(details)) = xpi.addon_type else {
+        panic!("expect a site permission add-on");
+    };
+    assert_eq!(vec!["midi", "midi-sysex"], details.site_permissions);
+    assert_eq!(vec!["https://example.com"], details.install_origins);
+}
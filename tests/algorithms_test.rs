@@ -0,0 +1,155 @@
+use cms::cert::x509::{
+    der::{asn1::Any, Decode},
+    spki::AlgorithmIdentifierOwned,
+};
+use const_oid::{
+    db::{
+        rfc5912::{
+            ECDSA_WITH_SHA_384, ID_RSASSA_PSS, ID_SHA_384, ID_SHA_512, RSA_ENCRYPTION,
+            SHA_1_WITH_RSA_ENCRYPTION,
+        },
+        rfc8410::ID_ED_25519,
+    },
+    ObjectIdentifier,
+};
+use xpidump::{Algorithm, DigestAlgorithm, SignatureAlgorithm, XPI};
+
+#[test]
+fn test_digest_algorithms() {
+    assert_eq!(
+        DigestAlgorithm::Sha384,
+        DigestAlgorithm::from_oid(&ID_SHA_384)
+    );
+    assert_eq!(
+        DigestAlgorithm::Sha512,
+        DigestAlgorithm::from_oid(&ID_SHA_512)
+    );
+    assert_eq!("SHA-512", DigestAlgorithm::Sha512.to_string());
+
+    // The OID of unknown algorithms is preserved.
+    let md5 = ObjectIdentifier::new_unwrap("1.2.840.113549.2.5");
+    assert_eq!(
+        DigestAlgorithm::Unknown("1.2.840.113549.2.5".to_owned()),
+        DigestAlgorithm::from_oid(&md5)
+    );
+    assert_eq!(
+        "\"1.2.840.113549.2.5\"",
+        serde_json::to_string(&DigestAlgorithm::from_oid(&md5)).unwrap()
+    );
+}
+
+/// Returns an algorithm identifier with DER-encoded parameters.
+fn identifier(oid: ObjectIdentifier, parameters: Option<&[u8]>) -> AlgorithmIdentifierOwned {
+    AlgorithmIdentifierOwned {
+        oid,
+        parameters: parameters.map(|der| Any::from_der(der).unwrap()),
+    }
+}
+
+#[test]
+fn test_signature_algorithms() {
+    for (oid, digest_algorithm, expected) in [
+        (
+            RSA_ENCRYPTION,
+            DigestAlgorithm::Sha256,
+            SignatureAlgorithm::RsaPkcs1Sha256,
+        ),
+        (
+            SHA_1_WITH_RSA_ENCRYPTION,
+            DigestAlgorithm::Sha256,
+            SignatureAlgorithm::RsaPkcs1Sha1,
+        ),
+        (
+            ECDSA_WITH_SHA_384,
+            DigestAlgorithm::Sha384,
+            SignatureAlgorithm::EcdsaSha384,
+        ),
+        (
+            ID_ED_25519,
+            DigestAlgorithm::Sha512,
+            SignatureAlgorithm::EdDsa,
+        ),
+    ] {
+        assert_eq!(
+            expected,
+            SignatureAlgorithm::from_algorithm_identifier(
+                &identifier(oid, None),
+                &digest_algorithm
+            )
+        );
+    }
+
+    // `rsaEncryption` with a digest algorithm that has no COSE name.
+    let md5 = DigestAlgorithm::Unknown("1.2.840.113549.2.5".to_owned());
+    assert_eq!(
+        SignatureAlgorithm::UnknownOid(RSA_ENCRYPTION.to_string()),
+        SignatureAlgorithm::from_algorithm_identifier(&identifier(RSA_ENCRYPTION, None), &md5)
+    );
+}
+
+#[test]
+fn test_rsassa_pss() {
+    // RSASSA-PSS-params with `hashAlgorithm` set to SHA-256.
+    let sha256 = [
+        0x30, 0x11, 0xa0, 0x0f, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04,
+        0x02, 0x01, 0x05, 0x00,
+    ];
+    let algorithm = SignatureAlgorithm::from_algorithm_identifier(
+        &identifier(ID_RSASSA_PSS, Some(&sha256)),
+        &DigestAlgorithm::Sha256,
+    );
+    assert_eq!(SignatureAlgorithm::RsaPssSha256, algorithm);
+    assert_eq!("PS256", algorithm.to_string());
+
+    // The digest algorithm defaults to SHA-1, which has no COSE name with RSASSA-PSS.
+    for parameters in [None, Some(&[0x30, 0x00][..])] {
+        let algorithm = SignatureAlgorithm::from_algorithm_identifier(
+            &identifier(ID_RSASSA_PSS, parameters),
+            &DigestAlgorithm::Sha256,
+        );
+        assert_eq!(
+            SignatureAlgorithm::UnknownOid("1.2.840.113549.1.1.10".to_owned()),
+            algorithm
+        );
+        assert_eq!(None, algorithm.cose_id());
+    }
+}
+
+#[test]
+fn test_cose_algorithms() {
+    for (id, name) in [
+        (-7, "ES256"),
+        (-36, "ES512"),
+        (-8, "EdDSA"),
+        (-37, "PS256"),
+        (-257, "RS256"),
+        (-65535, "RS1"),
+        (-999, "COSE(-999)"),
+    ] {
+        let algorithm = SignatureAlgorithm::from_cose(id);
+        assert_eq!(name, algorithm.to_string());
+        assert_eq!(Some(id), algorithm.cose_id());
+    }
+}
+
+#[test]
+fn test_signature_algorithm() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+
+    assert_eq!(
+        Some(Algorithm::Digest(DigestAlgorithm::Sha1)),
+        xpi.signatures.pkcs7.algorithm
+    );
+    assert_eq!(
+        Some(Algorithm::Signature(SignatureAlgorithm::EcdsaSha256)),
+        xpi.signatures.cose.algorithm
+    );
+
+    let json = serde_json::to_value(&xpi).unwrap();
+    assert_eq!("SHA-1", json["signatures"]["pkcs7"]["algorithm"]);
+    assert_eq!("ES256", json["signatures"]["cose"]["algorithm"]);
+    assert_eq!(
+        "RS1",
+        json["signatures"]["pkcs7"]["signers"][0]["signature_algorithm"]
+    );
+}
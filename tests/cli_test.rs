@@ -0,0 +1,88 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{self, Command, Output, Stdio},
+};
+
+/// Runs the CLI with `args`, writing `stdin` to its standard input.
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xpidump"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_success() {
+    let output = run(&["tests/fixtures/dev-new.xpi"], b"");
+
+    assert_eq!(Some(0), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("MANIFEST:"));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_missing_file() {
+    let output = run(&["tests/fixtures/does-not-exist.xpi"], b"");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(output.stdout.is_empty());
+    assert!(stderr(&output)
+        .starts_with("error: failed to open `tests/fixtures/does-not-exist.xpi`\n  caused by: "));
+}
+
+#[test]
+fn test_not_a_zip() {
+    let output = run(&["-"], b"not a zip file");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).starts_with(
+        "error: failed to read XPI file `-`\n  caused by: failed to read ZIP archive\n  caused by: "
+    ));
+}
+
+#[test]
+fn test_truncated_archive() {
+    let bytes = include_bytes!("fixtures/dev-new.xpi");
+    let output = run(&["-"], &bytes[..bytes.len() / 2]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("caused by: failed to read ZIP archive"));
+}
+
+#[test]
+fn test_subcommand_error() {
+    let output = run(&["diff", "tests/fixtures/dev-new.xpi", "-"], b"");
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).starts_with("error: failed to read XPI file `-`"));
+}
+
+#[test]
+fn test_failed_policy() {
+    let policy = env::temp_dir().join(format!("xpidump-cli-test-{}.json", process::id()));
+    fs::write(&policy, r#"{ "allowed_environments": ["production"] }"#).unwrap();
+    let output = run(
+        &[
+            "--policy",
+            policy.to_str().unwrap(),
+            "tests/fixtures/dev-new.xpi",
+        ],
+        b"",
+    );
+    fs::remove_file(&policy).unwrap();
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("POLICY: FAILED"));
+    assert!(output.stderr.is_empty());
+}
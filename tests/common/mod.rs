@@ -0,0 +1,54 @@
+use std::io::{Cursor, Write};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+/// Builds an in-memory XPI file containing the given files.
+#[allow(dead_code)]
+pub fn make_xpi(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        writer
+            .start_file(*name, FileOptions::default())
+            .expect("failed to start file");
+        writer.write_all(content).expect("failed to write file");
+    }
+
+    writer.finish().expect("failed to finish XPI").into_inner()
+}
+
+/// Copies a fixture, replacing the content of the files listed in `changes`. Files that do not
+/// exist in the fixture are appended.
+#[allow(dead_code)]
+pub fn patch_fixture(bytes: &[u8], changes: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for index in 0..archive.len() {
+        let file = archive.by_index(index).unwrap();
+        if changes.iter().any(|(name, _)| *name == file.name()) {
+            continue;
+        }
+        writer.raw_copy_file(file).unwrap();
+    }
+    for (name, content) in changes {
+        writer.start_file(*name, FileOptions::default()).unwrap();
+        writer.write_all(content).unwrap();
+    }
+
+    writer.finish().unwrap().into_inner()
+}
+
+/// Copies a fixture without the files listed in `names`.
+#[allow(dead_code)]
+pub fn remove_files(bytes: &[u8], names: &[&str]) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for index in 0..archive.len() {
+        let file = archive.by_index(index).unwrap();
+        if !names.contains(&file.name()) {
+            writer.raw_copy_file(file).unwrap();
+        }
+    }
+
+    writer.finish().unwrap().into_inner()
+}
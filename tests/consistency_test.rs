@@ -0,0 +1,79 @@
+mod common;
+
+use common::patch_fixture;
+use std::io::{Cursor, Read};
+use xpidump::{MismatchKind, XPI};
+use zip::ZipArchive;
+
+fn read_file(bytes: &[u8], name: &str) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut content = Vec::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    content
+}
+
+#[test]
+fn test_consistent_signatures() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+
+    assert!(xpi.signatures.mismatches.is_empty());
+    assert!(format!("{}", xpi).contains("  CONSISTENCY:\n   └── OK"));
+}
+
+#[test]
+fn test_single_signature() {
+    let xpi = XPI::from_path("tests/fixtures/colorzilla-3.3.xpi").unwrap();
+
+    assert!(xpi.signatures.mismatches.is_empty());
+    assert!(!format!("{}", xpi).contains("CONSISTENCY:"));
+}
+
+#[test]
+fn test_mismatched_signatures() {
+    // Use the COSE signature of another add-on signed with the staging root certificate.
+    let cose = read_file(include_bytes!("fixtures/dev-new.xpi"), "META-INF/cose.sig");
+    let bytes = patch_fixture(
+        include_bytes!("fixtures/amo_info-1.25.0.xpi"),
+        &[("META-INF/cose.sig", &cose)],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let mismatches = &xpi.signatures.mismatches;
+
+    assert_eq!(
+        vec![
+            MismatchKind::Environment,
+            MismatchKind::CommonName,
+            MismatchKind::Expiry,
+        ],
+        mismatches.iter().map(|m| m.kind).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        "environment: PKCS7 is PRODUCTION, COSE is STAGING",
+        mismatches[0].message
+    );
+}
+
+#[test]
+fn test_cose_not_covered() {
+    let bytes = patch_fixture(
+        include_bytes!("fixtures/amo_info-1.25.0.xpi"),
+        &[("META-INF/manifest.mf", b"Manifest-Version: 1.0\n")],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert_eq!(
+        vec![
+            "META-INF/cose.manifest is not covered by the PKCS#7 signature",
+            "META-INF/cose.sig is not covered by the PKCS#7 signature",
+        ],
+        xpi.signatures
+            .mismatches
+            .iter()
+            .map(|m| m.message.as_str())
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use cms::{
+    cert::{
+        x509::der::{Decode, Encode},
+        CertificateChoices,
+    },
+    content_info::ContentInfo,
+    signed_data::SignedData,
+};
+use common::make_xpi;
+use minicbor::{data::Tag, Encoder};
+use std::io::{Cursor, Read};
+use xpidump::{Algorithm, CoseQuirk, SignatureAlgorithm, XPI};
+use zip::ZipArchive;
+
+/// Returns the DER-encoded certificates of the PKCS#7 signature of a fixture, i.e. the
+/// end-entity certificate followed by the intermediate certificate.
+fn read_certificates(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .by_name("META-INF/mozilla.rsa")
+        .unwrap()
+        .read_to_end(&mut buffer)
+        .unwrap();
+
+    let content_info = ContentInfo::from_der(&buffer).unwrap();
+    let data = SignedData::from_der(&content_info.content.to_der().unwrap()).unwrap();
+    data.certificates
+        .unwrap()
+        .0
+        .iter()
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert.to_der().unwrap()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn make_cose_xpi(signature: &[u8]) -> Vec<u8> {
+    make_xpi(&[
+        ("manifest.json", br#"{ "manifest_version": 2 }"#),
+        ("META-INF/cose.manifest", b""),
+        ("META-INF/cose.sig", signature),
+    ])
+}
+
+#[test]
+fn test_mozilla_quirks() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+    let cose = &xpi.signatures.cose;
+
+    assert_eq!(
+        vec![CoseQuirk::KidArray, CoseQuirk::KidCertificate],
+        cose.quirks
+    );
+    assert!(xpi.signatures.pkcs7.quirks.is_empty());
+    assert!(format!("{}", xpi).contains(
+        "Quirks:\n        └── kid is an array of certificates\n        └── kid contains a certificate"
+    ));
+
+    let json = serde_json::to_value(&xpi).unwrap();
+    assert_eq!("kid_array", json["signatures"]["cose"]["quirks"][0]);
+}
+
+#[test]
+fn test_x5chain() {
+    let certificates = read_certificates(include_bytes!("fixtures/amo_info-1.25.0.xpi"));
+
+    let mut protected = Encoder::new(vec![]);
+    // Additional headers, in any order and with text labels.
+    protected
+        .map(3)
+        .unwrap()
+        .u8(4)
+        .unwrap()
+        .bytes(b"key-id")
+        .unwrap()
+        .str("custom")
+        .unwrap()
+        .str("value")
+        .unwrap()
+        .u8(1)
+        .unwrap()
+        .i64(-7)
+        .unwrap();
+
+    let mut encoder = Encoder::new(vec![]);
+    encoder
+        .tag(Tag::new(98))
+        .unwrap()
+        .array(4)
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .map(0)
+        .unwrap()
+        .null()
+        .unwrap()
+        .array(1)
+        .unwrap()
+        .array(3)
+        .unwrap()
+        .bytes(protected.writer())
+        .unwrap()
+        // The certificate chain in the unprotected headers.
+        .map(1)
+        .unwrap()
+        .u8(33)
+        .unwrap()
+        .array(2)
+        .unwrap()
+        .bytes(&certificates[0])
+        .unwrap()
+        .bytes(&certificates[1])
+        .unwrap()
+        .bytes(b"signature")
+        .unwrap();
+
+    let xpi = XPI::from_bytes(&make_cose_xpi(encoder.writer())).unwrap();
+    let cose = &xpi.signatures.cose;
+
+    assert!(cose.exists());
+    assert_eq!(
+        Some(Algorithm::Signature(SignatureAlgorithm::EcdsaSha256)),
+        cose.algorithm
+    );
+    assert_eq!(
+        vec![
+            "signingca1.addons.mozilla.org",
+            "{db55bb9b-0d9f-407f-9b65-da9dd29c8d32}"
+        ],
+        cose.certificates
+            .iter()
+            .map(|cert| cert.common_name.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(cose.quirks.is_empty());
+}
+
+#[test]
+fn test_indefinite_length() {
+    let certificates = read_certificates(include_bytes!("fixtures/amo_info-1.25.0.xpi"));
+
+    let mut protected = Encoder::new(vec![]);
+    protected
+        .begin_map()
+        .unwrap()
+        .u8(1)
+        .unwrap()
+        .i64(-35)
+        .unwrap()
+        .end()
+        .unwrap();
+
+    // An untagged `COSE_Sign` structure.
+    let mut encoder = Encoder::new(vec![]);
+    encoder
+        .begin_array()
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .begin_map()
+        .unwrap()
+        .u8(33)
+        .unwrap()
+        .begin_bytes()
+        .unwrap()
+        .bytes(&certificates[1][..10])
+        .unwrap()
+        .bytes(&certificates[1][10..])
+        .unwrap()
+        .end()
+        .unwrap()
+        .end()
+        .unwrap()
+        .null()
+        .unwrap()
+        .begin_array()
+        .unwrap()
+        .begin_array()
+        .unwrap()
+        .bytes(protected.writer())
+        .unwrap()
+        .map(0)
+        .unwrap()
+        .bytes(b"signature")
+        .unwrap()
+        .end()
+        .unwrap()
+        .end()
+        .unwrap()
+        .end()
+        .unwrap();
+
+    let xpi = XPI::from_bytes(&make_cose_xpi(encoder.writer())).unwrap();
+    let cose = &xpi.signatures.cose;
+
+    assert_eq!(
+        Some(Algorithm::Signature(SignatureAlgorithm::EcdsaSha384)),
+        cose.algorithm
+    );
+    assert_eq!(1, cose.certificates.len());
+    assert_eq!(
+        "signingca1.addons.mozilla.org",
+        cose.certificates[0].common_name
+    );
+    assert!(cose.quirks.is_empty());
+}
+
+#[test]
+fn test_missing_algorithm() {
+    // A `COSE_Sign` structure with a signer that has no `alg` header.
+    let mut encoder = Encoder::new(vec![]);
+    encoder
+        .tag(Tag::new(98))
+        .unwrap()
+        .array(4)
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .map(0)
+        .unwrap()
+        .null()
+        .unwrap()
+        .array(1)
+        .unwrap()
+        .array(3)
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .map(0)
+        .unwrap()
+        .bytes(b"signature")
+        .unwrap();
+
+    let xpi = XPI::from_bytes(&make_cose_xpi(encoder.writer())).unwrap();
+    let cose = &xpi.signatures.cose;
+
+    assert_eq!(Some(Algorithm::Unknown), cose.algorithm);
+    let json = serde_json::to_value(&xpi).unwrap();
+    assert_eq!("UNKNOWN", json["signatures"]["cose"]["algorithm"]);
+}
+
+#[test]
+fn test_invalid_tag() {
+    let mut encoder = Encoder::new(vec![]);
+    encoder.tag(Tag::new(18)).unwrap().array(0).unwrap();
+
+    let xpi = XPI::from_bytes(&make_cose_xpi(encoder.writer())).unwrap();
+    let cose = &xpi.signatures.cose;
+
+    // The signature files are present but the signature cannot be parsed.
+    assert!(cose.exists());
+    assert!(cose.certificates.is_empty());
+    assert_eq!(None, cose.algorithm);
+}
@@ -0,0 +1,102 @@
+use sha2::{Digest, Sha256};
+use xpidump::{KeyProofAlgorithm, CRX};
+
+fn proto_field(field: u64, value: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut key = (field << 3) | 2;
+    while key >= 0x80 {
+        bytes.push((key as u8) | 0x80);
+        key >>= 7;
+    }
+    bytes.push(key as u8);
+    // Values are always shorter than 128 bytes in these tests.
+    bytes.push(value.len() as u8);
+    bytes.extend_from_slice(value);
+    bytes
+}
+
+fn make_crx(developer_key: &[u8], store_key: &[u8]) -> Vec<u8> {
+    let crx_id = &Sha256::digest(developer_key)[..16];
+
+    let mut header = vec![];
+    header.extend(proto_field(
+        2,
+        &[proto_field(1, store_key), proto_field(2, b"rsa-signature")].concat(),
+    ));
+    header.extend(proto_field(
+        3,
+        &[
+            proto_field(1, developer_key),
+            proto_field(2, b"ecdsa-signature"),
+        ]
+        .concat(),
+    ));
+    header.extend(proto_field(10000, &proto_field(1, crx_id)));
+
+    let mut crx = b"Cr24".to_vec();
+    crx.extend(3u32.to_le_bytes());
+    crx.extend((header.len() as u32).to_le_bytes());
+    crx.extend(header);
+    crx.extend(include_bytes!("fixtures/unsigned.zip"));
+    crx
+}
+
+#[test]
+fn test_crx() {
+    let bytes = make_crx(b"developer public key", b"store public key");
+    assert!(CRX::is_crx(&bytes));
+
+    let crx = CRX::from_bytes(&bytes).unwrap();
+
+    assert!(crx.manifest.exists());
+    assert_eq!(
+        "1.0",
+        crx.manifest.version.as_ref().expect("expect version")
+    );
+    assert_eq!(1, crx.entries.len());
+
+    let crx_id = crx.header.crx_id.as_ref().expect("expect CRX ID");
+    assert_eq!(32, crx_id.len());
+    let extension_id = crx.header.extension_id.as_ref().expect("expect ID");
+    assert_eq!(32, extension_id.len());
+    assert!(extension_id.chars().all(|c| ('a'..='p').contains(&c)));
+
+    assert_eq!(2, crx.header.proofs.len());
+    assert_eq!(
+        KeyProofAlgorithm::Sha256WithRsa,
+        crx.header.proofs[0].algorithm
+    );
+    assert!(!crx.header.proofs[0].developer_key);
+    assert_eq!(
+        KeyProofAlgorithm::Sha256WithEcdsa,
+        crx.header.proofs[1].algorithm
+    );
+    assert!(crx.header.proofs[1].developer_key);
+    // "developer public key" hex-encoded.
+    assert!(crx.header.proofs[1]
+        .public_key
+        .starts_with("646576656c6f706572"));
+}
+
+#[test]
+fn test_invalid_crx() {
+    assert!(!CRX::is_crx(include_bytes!("fixtures/unsigned.zip")));
+    assert!(matches!(
+        CRX::from_bytes(include_bytes!("fixtures/unsigned.zip")),
+        Err(xpidump::Error::InvalidCrxHeader)
+    ));
+
+    // Header size larger than the file.
+    let mut bytes = b"Cr24".to_vec();
+    bytes.extend(3u32.to_le_bytes());
+    bytes.extend(1000u32.to_le_bytes());
+    assert!(matches!(
+        CRX::from_bytes(&bytes),
+        Err(xpidump::Error::InvalidCrxHeader)
+    ));
+
+    // CRX2 files are not supported.
+    let mut bytes = make_crx(b"key", b"key");
+    bytes[4] = 2;
+    assert!(CRX::from_bytes(&bytes).is_err());
+}
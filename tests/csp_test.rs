@@ -0,0 +1,105 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{ContentSecurityPolicy, CspContext, CspWeaknessKind, XPI};
+
+fn weaknesses(csp: &ContentSecurityPolicy) -> Vec<(CspWeaknessKind, Option<&str>, Option<&str>)> {
+    csp.weaknesses
+        .iter()
+        .map(|w| (w.kind, w.directive.as_deref(), w.source.as_deref()))
+        .collect()
+}
+
+#[test]
+fn test_mv2_policy() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "content_security_policy": "script-src 'self' 'unsafe-eval' https://cdn.example.com; style-src 'self' 'unsafe-inline'"
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    assert_eq!(1, xpi.manifest.content_security_policy.len());
+    let csp = &xpi.manifest.content_security_policy[0];
+    assert_eq!(CspContext::ExtensionPages, csp.context);
+    assert_eq!(
+        vec!["'self'", "'unsafe-eval'", "https://cdn.example.com"],
+        csp.directive("script-src").unwrap().sources
+    );
+    assert_eq!(
+        vec![
+            (
+                CspWeaknessKind::UnsafeEval,
+                Some("script-src"),
+                Some("'unsafe-eval'")
+            ),
+            (
+                CspWeaknessKind::RemoteScript,
+                Some("script-src"),
+                Some("https://cdn.example.com")
+            ),
+            (CspWeaknessKind::MissingObjectSrc, None, None),
+        ],
+        weaknesses(csp)
+    );
+}
+
+#[test]
+fn test_mv3_policies() {
+    let manifest = br#"{
+      "manifest_version": 3,
+      "name": "An extension",
+      "version": "1.0",
+      "content_security_policy": {
+        "extension_pages": "script-src 'self' 'wasm-unsafe-eval'; object-src 'self'",
+        "sandbox": "sandbox allow-scripts; default-src *; script-src 'self' 'unsafe-inline' https:"
+      }
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    let policies = &xpi.manifest.content_security_policy;
+    assert_eq!(2, policies.len());
+    assert_eq!(CspContext::ExtensionPages, policies[0].context);
+    assert_eq!(
+        vec![(
+            CspWeaknessKind::WasmUnsafeEval,
+            Some("script-src"),
+            Some("'wasm-unsafe-eval'")
+        )],
+        weaknesses(&policies[0])
+    );
+    assert_eq!(CspContext::Sandbox, policies[1].context);
+    assert_eq!(
+        vec![
+            (
+                CspWeaknessKind::WildcardSource,
+                Some("default-src"),
+                Some("*")
+            ),
+            (
+                CspWeaknessKind::UnsafeInline,
+                Some("script-src"),
+                Some("'unsafe-inline'")
+            ),
+            (
+                CspWeaknessKind::WildcardSource,
+                Some("script-src"),
+                Some("https:")
+            ),
+            (
+                CspWeaknessKind::RemoteScript,
+                Some("script-src"),
+                Some("https:")
+            ),
+        ],
+        weaknesses(&policies[1])
+    );
+}
+
+#[test]
+fn test_no_policy() {
+    let xpi = XPI::from_bytes(include_bytes!("fixtures/amo_info-1.25.0.xpi")).unwrap();
+
+    assert!(xpi.manifest.content_security_policy.is_empty());
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::make_xpi;
+use std::io::{Cursor, Write};
+use xpidump::{Change, XPI};
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+fn load(bytes: &[u8]) -> XPI {
+    let reader = Cursor::new(bytes);
+    let mut archive = ZipArchive::new(reader).unwrap();
+
+    XPI::new(&mut archive)
+}
+
+/// Builds an XPI file whose `data.txt` file cannot be read, because its content does not match
+/// its CRC-32.
+fn corrupted_xpi(content: &[u8]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file("data.txt", options).unwrap();
+    writer.write_all(content).unwrap();
+    let mut bytes = writer.finish().unwrap().into_inner();
+
+    let offset = bytes
+        .windows(content.len())
+        .position(|window| window == content)
+        .unwrap();
+    bytes[offset] ^= 0xff;
+    bytes
+}
+
+#[test]
+fn test_diff_same_xpi() {
+    let old = load(include_bytes!("fixtures/dev-new.xpi"));
+    let new = load(include_bytes!("fixtures/dev-new.xpi"));
+
+    assert!(old.diff(&new).is_empty());
+}
+
+#[test]
+fn test_diff() {
+    let old = load(include_bytes!("fixtures/dev-new.xpi"));
+    let new = load(include_bytes!("fixtures/line-staging-cas-cur.xpi"));
+
+    let diff = old.diff(&new);
+
+    assert!(!diff.is_empty());
+    assert!(diff.manifest.contains(&Change {
+        field: "version".to_owned(),
+        old: Some("16.0".to_owned()),
+        new: Some("109.2".to_owned()),
+    }));
+    assert!(diff.permissions.added.is_empty());
+    assert!(diff.permissions.removed.is_empty());
+    // Both XPI files are signed with the same staging intermediate.
+    assert!(!diff
+        .signatures
+        .iter()
+        .any(|change| change.field == "pkcs7.env" || change.field == "cose.env"));
+    assert!(diff
+        .signatures
+        .iter()
+        .any(|change| change.field == "pkcs7.certificates[1]"));
+    assert_eq!(
+        vec!["states".to_owned(), "validity".to_owned()],
+        diff.recommendation
+            .iter()
+            .map(|change| change.field.clone())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(vec!["mozilla-recommendation.json"], diff.entries.added);
+    assert!(diff.entries.removed.is_empty());
+    assert!(diff.entries.modified.contains(&"manifest.json".to_owned()));
+}
+
+#[test]
+fn test_diff_signatures_and_permissions() {
+    let old = load(include_bytes!("fixtures/unsigned.zip"));
+    let new = load(include_bytes!("fixtures/amo_info-1.25.0.xpi"));
+
+    let diff = old.diff(&new);
+
+    assert!(diff.signatures.contains(&Change {
+        field: "pkcs7.present".to_owned(),
+        old: Some("false".to_owned()),
+        new: Some("true".to_owned()),
+    }));
+    assert!(diff.signatures.contains(&Change {
+        field: "cose.env".to_owned(),
+        old: Some("UNKNOWN".to_owned()),
+        new: Some("PRODUCTION".to_owned()),
+    }));
+    assert!(diff.permissions.added.contains(&"storage".to_owned()));
+    assert!(diff.entries.added.contains(&"background.js".to_owned()));
+    assert!(diff.entries.removed.is_empty());
+}
+
+#[test]
+fn test_diff_source_maps() {
+    let old = load(&make_xpi(&[("app.js", b"var a;")]));
+    let new = load(&make_xpi(&[
+        ("app.js", b"var a;\n//# sourceMappingURL=app.js.map"),
+        (
+            "app.js.map",
+            br#"{"version":3,"sources":["app.ts"],"mappings":""}"#,
+        ),
+    ]));
+
+    let diff = old.diff(&new);
+
+    assert_eq!(vec!["app.js.map"], diff.entries.added);
+    assert!(format!("{}", diff).ends_with("ENTRIES:\n  + app.js.map (source map)\n  ~ app.js"));
+}
+
+#[test]
+fn test_diff_unreadable_entries() {
+    let old = load(&corrupted_xpi(b"old content"));
+    let new = load(&corrupted_xpi(b"new content"));
+    assert_eq!("", old.entries[0].sha256);
+
+    // The content of the files is unknown, so they cannot be considered unchanged.
+    let diff = old.diff(&new);
+    assert_eq!(vec!["data.txt"], diff.entries.modified);
+
+    let readable = load(&make_xpi(&[("data.txt", b"old content")]));
+    assert_eq!(vec!["data.txt"], readable.diff(&new).entries.modified);
+    assert_eq!(vec!["data.txt"], old.diff(&readable).entries.modified);
+}
@@ -0,0 +1,29 @@
+#!/bin/sh
+# Generates the test keys and certificates used to sign XPI files in the tests, mirroring the AMO
+# layout: a root certificate, an intermediate certificate and an end-entity certificate whose
+# common name is the add-on ID. Only the end-entity key and the chain are kept.
+set -e
+cd "$(dirname "$0")"
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out "$tmp/root.key"
+openssl req -x509 -new -key "$tmp/root.key" -sha256 -days 36500 \
+  -subj "/O=xpidump/OU=Testing/CN=xpidump test root" \
+  -addext "basicConstraints=critical,CA:TRUE" -out "$tmp/root.pem"
+
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out "$tmp/intermediate.key"
+openssl req -new -key "$tmp/intermediate.key" \
+  -subj "/O=xpidump/OU=Testing/CN=xpidump test intermediate" -out "$tmp/intermediate.csr"
+printf "basicConstraints=critical,CA:TRUE\n" > "$tmp/intermediate.ext"
+openssl x509 -req -in "$tmp/intermediate.csr" -CA "$tmp/root.pem" -CAkey "$tmp/root.key" \
+  -set_serial 1 -sha256 -days 36500 -extfile "$tmp/intermediate.ext" -out "$tmp/intermediate.pem"
+
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out key.pem
+openssl req -new -key key.pem -subj "/O=xpidump/OU=Testing/CN=test@xpidump" -out "$tmp/ee.csr"
+printf "keyUsage=critical,digitalSignature\nextendedKeyUsage=codeSigning\n" > "$tmp/ee.ext"
+openssl x509 -req -in "$tmp/ee.csr" -CA "$tmp/intermediate.pem" -CAkey "$tmp/intermediate.key" \
+  -set_serial 2 -sha256 -days 36500 -extfile "$tmp/ee.ext" -out "$tmp/ee.pem"
+
+cat "$tmp/ee.pem" "$tmp/intermediate.pem" > chain.pem
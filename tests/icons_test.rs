@@ -0,0 +1,126 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{IconSource, ImageFormat, XPI};
+
+/// Builds the beginning of a PNG image, which is enough to read its dimensions.
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut content = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    content.extend(width.to_be_bytes());
+    content.extend(height.to_be_bytes());
+    content.extend(b"\x08\x06\0\0\0");
+    content
+}
+
+#[test]
+fn test_icons() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "icons": { "96": "icons/icon-96.png", "48": "/icons/icon-48.png", "128": "missing.png" },
+      "browser_action": { "default_icon": "icons/icon.svg" },
+      "page_action": { "default_icon": { "32": "icons/icon-48.png", "16": "README.md" } }
+    }"#;
+    let svg =
+        br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"/>"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[
+        ("manifest.json", manifest),
+        ("icons/icon-48.png", &png(48, 48)),
+        ("icons/icon-96.png", &png(96, 96)),
+        ("icons/icon.svg", svg),
+        ("README.md", b"# An extension"),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            (IconSource::Icons, Some(48), "icons/icon-48.png"),
+            (IconSource::Icons, Some(96), "icons/icon-96.png"),
+            (IconSource::Icons, Some(128), "missing.png"),
+            (IconSource::BrowserAction, None, "icons/icon.svg"),
+            (IconSource::PageAction, Some(16), "README.md"),
+            (IconSource::PageAction, Some(32), "icons/icon-48.png"),
+        ],
+        xpi.icons
+            .iter()
+            .map(|icon| (icon.source, icon.size, icon.path.as_str()))
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(Some(ImageFormat::Png), xpi.icons[0].format);
+    assert!(xpi.icons[0].is_valid());
+    assert_eq!(vec!["file not found"], xpi.icons[2].problems);
+    assert_eq!(Some(ImageFormat::Svg), xpi.icons[3].format);
+    assert_eq!(
+        (Some(24), Some(24)),
+        (xpi.icons[3].width, xpi.icons[3].height)
+    );
+    assert!(xpi.icons[3].is_valid());
+    assert_eq!(vec!["not a PNG or SVG image"], xpi.icons[4].problems);
+    assert_eq!(
+        vec!["expected 32x32 pixels, found 48x48"],
+        xpi.icons[5].problems
+    );
+
+    let output = format!("{}", xpi);
+    assert!(output.contains("ICONS:\n  icons.48: icons/icon-48.png (PNG, 48x48)"));
+    assert!(output.contains("browser_action.default_icon: icons/icon.svg (SVG, 24x24)"));
+}
+
+#[test]
+fn test_icon() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "icons": { "48": "icon-48.png", "96": "icon-96.png", "128": "icon-128.png" },
+      "browser_action": { "default_icon": { "64": "icon-64.png" } }
+    }"#;
+    let icon_48 = png(48, 48);
+    let bytes = make_xpi(&[
+        ("manifest.json", manifest),
+        ("icon-48.png", &icon_48),
+        ("icon-64.png", &png(64, 64)),
+        ("icon-96.png", &png(96, 96)),
+        // This icon is not valid.
+        ("icon-128.png", &png(64, 64)),
+    ]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert_eq!("icon-48.png", xpi.icon(32).unwrap().path);
+    assert_eq!("icon-48.png", xpi.icon(48).unwrap().path);
+    assert_eq!("icon-96.png", xpi.icon(64).unwrap().path);
+    assert_eq!("icon-96.png", xpi.icon(256).unwrap().path);
+    assert_eq!(
+        icon_48,
+        xpi.icon(16).unwrap().read_from_bytes(&bytes).unwrap()
+    );
+}
+
+#[test]
+fn test_action_icon_fallback() {
+    let manifest = br#"{
+      "manifest_version": 3,
+      "name": "An extension",
+      "version": "1.0",
+      "action": { "default_icon": "icon.svg" }
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[
+        ("manifest.json", manifest),
+        ("icon.svg", br#"<svg width="16px" height="16px"></svg>"#),
+    ]))
+    .unwrap();
+
+    let icon = xpi.icon(64).unwrap();
+    assert_eq!(IconSource::Action, icon.source);
+    assert_eq!((Some(16), Some(16)), (icon.width, icon.height));
+}
+
+#[test]
+fn test_no_icons() {
+    let xpi = XPI::from_path("tests/fixtures/webcompat.xpi").unwrap();
+
+    assert!(xpi.icons.is_empty());
+    assert!(xpi.icon(64).is_none());
+}
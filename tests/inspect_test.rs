@@ -0,0 +1,126 @@
+mod common;
+
+use common::{make_xpi, patch_fixture};
+use std::io::{Cursor, Read};
+use xpidump::Inspection;
+use zip::ZipArchive;
+
+fn read_file(bytes: &[u8], name: &str) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_end(&mut buffer)
+        .unwrap();
+
+    buffer
+}
+
+#[test]
+fn test_inspect() {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+    let inspection = Inspection::from_bytes(bytes).unwrap();
+
+    let cose = inspection.cose.as_deref().unwrap();
+    assert!(cose.starts_with("98([\n  <<{\n    4: [\n      h'3082"));
+    assert!(cose.contains("<<{\n        1: -7,\n        4: h'3082"));
+    assert!(cose.ends_with("]\n  ]\n])"));
+    assert_eq!(None, inspection.cose_error);
+
+    let pkcs7 = inspection.pkcs7.as_ref().unwrap();
+    assert_eq!(1, pkcs7.len());
+    assert_eq!("SEQUENCE", pkcs7[0].tag);
+    assert_eq!(0, pkcs7[0].offset);
+    assert_eq!(
+        Some("1.2.840.113549.1.7.2 (id-signedData)"),
+        pkcs7[0].children[0].value.as_deref()
+    );
+    assert_eq!(None, inspection.pkcs7_error);
+
+    let text = format!("{}", inspection);
+    assert!(text.contains(
+        "PKCS7 (META-INF/mozilla.rsa):\n  └── SEQUENCE (offset 0, length 4353)\n       └── \
+        OBJECT IDENTIFIER (offset 4, length 9): 1.2.840.113549.1.7.2 (id-signedData)"
+    ));
+    // DER-encoded extensions are decoded.
+    assert!(text.contains(
+        "OBJECT IDENTIFIER (offset 1017, length 8): 1.3.6.1.5.5.7.3.3 (id-kp-codeSigning)"
+    ));
+}
+
+#[test]
+fn test_inspect_truncated() {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+    let cose = read_file(bytes, "META-INF/cose.sig");
+    let pkcs7 = read_file(bytes, "META-INF/mozilla.rsa");
+    let bytes = patch_fixture(
+        bytes,
+        &[
+            ("META-INF/cose.sig", &cose[..20]),
+            ("META-INF/mozilla.rsa", &pkcs7[..100]),
+        ],
+    );
+    let inspection = Inspection::from_bytes(&bytes).unwrap();
+
+    assert_eq!(Some("98([\n  "), inspection.cose.as_deref());
+    assert!(inspection.cose_error.is_some());
+
+    // The elements are listed up to the error.
+    let pkcs7 = inspection.pkcs7.as_ref().unwrap();
+    assert_eq!("SEQUENCE", pkcs7[0].tag);
+    assert_eq!(2, pkcs7[0].children.len());
+    assert!(inspection
+        .pkcs7_error
+        .as_deref()
+        .unwrap()
+        .starts_with("invalid element at offset 99: "));
+    assert!(format!("{}", inspection).contains("\n  ERROR: invalid element at offset"));
+}
+
+#[test]
+fn test_inspect_unsigned() {
+    let inspection = Inspection::from_bytes(&make_xpi(&[("manifest.json", b"{}")])).unwrap();
+
+    assert_eq!(None, inspection.cose);
+    assert_eq!(None, inspection.pkcs7);
+    assert_eq!(
+        "COSE (META-INF/cose.sig):\n  NONE\n\nPKCS7 (META-INF/mozilla.rsa):\n  NONE",
+        format!("{}", inspection)
+    );
+}
+
+#[test]
+fn test_inspect_deeply_nested() {
+    // 200 KB of one-item arrays in the COSE signature.
+    let cose = vec![0x81; 200_000];
+
+    // 1000 nested sequences in the PKCS#7 signature.
+    let mut pkcs7 = vec![0x30, 0x00];
+    for _ in 0..1000 {
+        let length = (pkcs7.len() as u16).to_be_bytes();
+        pkcs7.splice(0..0, [0x30, 0x82, length[0], length[1]]);
+    }
+
+    let bytes = make_xpi(&[
+        ("META-INF/cose.sig", &cose),
+        ("META-INF/mozilla.rsa", &pkcs7),
+    ]);
+    let inspection = Inspection::from_bytes(&bytes).unwrap();
+
+    assert_eq!(
+        Some("decode error at position 65: more than 64 nested items"),
+        inspection.cose_error.as_deref()
+    );
+    assert!(inspection.cose.as_deref().unwrap().starts_with("[\n  [\n"));
+
+    assert_eq!(
+        Some("more than 64 nested elements at offset 260"),
+        inspection.pkcs7_error.as_deref()
+    );
+    let mut node = &inspection.pkcs7.as_ref().unwrap()[0];
+    for _ in 0..64 {
+        node = &node.children[0];
+    }
+    assert!(node.children.is_empty());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{AddonFormat, TargetApplication, XPI};
+
+const BOOTSTRAPPED_INSTALL_RDF: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<RDF xmlns="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+     xmlns:em="http://www.mozilla.org/2004/em-rdf#">
+  <Description about="urn:mozilla:install-manifest">
+    <em:id>legacy@example.com</em:id>
+    <em:version>2.1</em:version>
+    <em:type>2</em:type>
+    <em:bootstrap>true</em:bootstrap>
+    <em:name>Legacy &amp; Bootstrapped</em:name>
+    <em:targetApplication>
+      <Description>
+        <em:id>{ec8030f7-c20a-464f-9b0e-13a3a9e97384}</em:id>
+        <em:minVersion>38.0</em:minVersion>
+        <em:maxVersion>56.*</em:maxVersion>
+      </Description>
+    </em:targetApplication>
+    <em:targetApplication>
+      <Description em:id="{aa3c5121-dab2-40e2-81ca-7ea25febc110}"
+                   em:minVersion="38.0"
+                   em:maxVersion="56.*" />
+    </em:targetApplication>
+    <em:localized>
+      <Description>
+        <em:locale>fr</em:locale>
+        <em:name>Ancien</em:name>
+      </Description>
+    </em:localized>
+  </Description>
+</RDF>"#;
+
+#[test]
+fn test_bootstrapped_addon() {
+    let bytes = make_xpi(&[("install.rdf", BOOTSTRAPPED_INSTALL_RDF.as_bytes())]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert!(!xpi.manifest.exists());
+    assert_eq!(AddonFormat::Bootstrapped, xpi.format);
+
+    let legacy = xpi.legacy_manifest.expect("expect legacy manifest");
+    assert!(legacy.has_install_rdf);
+    assert!(!legacy.has_chrome_manifest);
+    assert!(!legacy.has_bootstrap_js);
+    assert_eq!("legacy@example.com", legacy.id.expect("expect ID"));
+    assert_eq!("2.1", legacy.version.expect("expect version"));
+    assert_eq!("Legacy & Bootstrapped", legacy.name.expect("expect name"));
+    assert_eq!(Some(2), legacy.addon_type);
+    assert!(legacy.bootstrap);
+    assert_eq!(
+        vec![
+            TargetApplication {
+                id: Some("{ec8030f7-c20a-464f-9b0e-13a3a9e97384}".to_owned()),
+                min_version: Some("38.0".to_owned()),
+                max_version: Some("56.*".to_owned()),
+            },
+            TargetApplication {
+                id: Some("{aa3c5121-dab2-40e2-81ca-7ea25febc110}".to_owned()),
+                min_version: Some("38.0".to_owned()),
+                max_version: Some("56.*".to_owned()),
+            },
+        ],
+        legacy.target_applications
+    );
+}
+
+#[test]
+fn test_legacy_xul_addon() {
+    let install_rdf = r#"<?xml version="1.0"?>
+<RDF:RDF xmlns:RDF="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+         xmlns:em="http://www.mozilla.org/2004/em-rdf#">
+  <RDF:Description RDF:about="urn:mozilla:install-manifest"
+                   em:id="xul@example.com"
+                   em:version="1.0" />
+</RDF:RDF>"#;
+    let bytes = make_xpi(&[
+        ("install.rdf", install_rdf.as_bytes()),
+        ("chrome.manifest", b"content xul chrome/content/"),
+    ]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert_eq!(AddonFormat::LegacyXul, xpi.format);
+    let legacy = xpi.legacy_manifest.expect("expect legacy manifest");
+    assert!(legacy.has_chrome_manifest);
+    assert_eq!("xul@example.com", legacy.id.expect("expect ID"));
+    assert_eq!("1.0", legacy.version.expect("expect version"));
+    assert!(!legacy.bootstrap);
+}
+
+#[test]
+fn test_bootstrap_js() {
+    let install_rdf = r#"<?xml version="1.0"?>
+<RDF xmlns="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+     xmlns:em="http://www.mozilla.org/2004/em-rdf#">
+  <Description about="urn:mozilla:install-manifest">
+    <em:id>bootstrap@example.com</em:id>
+    <em:version>1.0</em:version>
+  </Description>
+</RDF>"#;
+    let bytes = make_xpi(&[
+        ("install.rdf", install_rdf.as_bytes()),
+        ("bootstrap.js", b"function startup() {}"),
+    ]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    // There is no `em:bootstrap` property but `bootstrap.js` exists.
+    assert_eq!(AddonFormat::Bootstrapped, xpi.format);
+    let legacy = xpi.legacy_manifest.expect("expect legacy manifest");
+    assert!(!legacy.bootstrap);
+    assert!(legacy.has_bootstrap_js);
+    assert!(format!("{}", legacy).contains("\n  bootstrap.js   : Yes\n"));
+
+    // A `bootstrap.js` file alone is not a legacy add-on.
+    let bytes = make_xpi(&[("bootstrap.js", b"")]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert_eq!(AddonFormat::Unknown, xpi.format);
+    assert!(xpi.legacy_manifest.is_none());
+}
+
+#[test]
+fn test_legacy_types() {
+    for (addon_type, format) in [
+        ("4", AddonFormat::Theme),
+        ("8", AddonFormat::LanguagePack),
+        ("64", AddonFormat::Dictionary),
+    ] {
+        let install_rdf = format!(
+            r#"<RDF xmlns:em="http://www.mozilla.org/2004/em-rdf#"><Description><em:type>{}</em:type></Description></RDF>"#,
+            addon_type
+        );
+        let bytes = make_xpi(&[("install.rdf", install_rdf.as_bytes())]);
+        let xpi = XPI::from_bytes(&bytes).unwrap();
+
+        assert_eq!(format, xpi.format);
+    }
+}
+
+#[test]
+fn test_webextension_format() {
+    let xpi = XPI::from_bytes(include_bytes!("fixtures/dev-old-recommended.xpi")).unwrap();
+
+    assert_eq!(AddonFormat::WebExtension, xpi.format);
+    assert!(xpi.legacy_manifest.is_none());
+
+    let bytes = make_xpi(&[("hello.txt", b"hello")]);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+
+    assert_eq!(AddonFormat::Unknown, xpi.format);
+}
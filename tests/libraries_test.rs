@@ -0,0 +1,93 @@
+use xpidump::{detect_libraries, DetectionMethod, ScanReport};
+
+#[test]
+fn test_vendored_libraries() {
+    let report = ScanReport::from_bytes(include_bytes!("fixtures/colorzilla-3.3.xpi")).unwrap();
+
+    let libraries: Vec<(&str, &str, &str, DetectionMethod, bool)> = report
+        .libraries
+        .iter()
+        .map(|lib| {
+            (
+                lib.path.as_str(),
+                lib.name.as_str(),
+                lib.version.as_str(),
+                lib.method,
+                lib.outdated,
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                "lib/jquery.js",
+                "jQuery",
+                "3.1.1",
+                DetectionMethod::Hash,
+                true
+            ),
+            (
+                "lib/underscore.js",
+                "Underscore.js",
+                "1.8.3",
+                DetectionMethod::Banner,
+                true
+            ),
+        ],
+        libraries
+    );
+}
+
+#[test]
+fn test_bundled_libraries() {
+    let report =
+        ScanReport::from_bytes(include_bytes!("fixtures/laboratory_by_mozilla-3.0.8.xpi")).unwrap();
+
+    let libraries: Vec<(&str, &str)> = report
+        .libraries
+        .iter()
+        .filter(|lib| lib.path == "popup/popup.js")
+        .map(|lib| (lib.name.as_str(), lib.version.as_str()))
+        .collect();
+    // Bootstrap's error message mentions `jQuery v1.9.1`, which must not be reported.
+    assert_eq!(
+        vec![
+            ("jQuery", "3.5.1"),
+            ("Sizzle", "2.3.5"),
+            ("Bootstrap", "4.6.0"),
+            ("clipboard.js", "2.0.6"),
+        ],
+        libraries
+    );
+}
+
+#[test]
+fn test_release_hashes() {
+    // The file is an unmodified release, its hash is listed in the database.
+    let release = include_bytes!("fixtures/clipboard-2.0.4.min.js");
+    let libraries = detect_libraries("vendor/clipboard.min.js", release);
+    assert_eq!(1, libraries.len());
+    assert_eq!("clipboard.js", libraries[0].name);
+    assert_eq!("2.0.4", libraries[0].version);
+    assert_eq!(DetectionMethod::Hash, libraries[0].method);
+
+    // Any change to the file falls back to the banner.
+    let mut modified = release.to_vec();
+    modified.extend_from_slice(b"\nconsole.log('modified');\n");
+    let libraries = detect_libraries("vendor/clipboard.min.js", &modified);
+    assert_eq!(1, libraries.len());
+    assert_eq!("2.0.4", libraries[0].version);
+    assert_eq!(DetectionMethod::Banner, libraries[0].method);
+}
+
+#[test]
+fn test_required_markers() {
+    let lodash = b"/** @license lodash <https://lodash.com/> */ var VERSION = '4.17.15';";
+    let libraries = detect_libraries("lodash.js", lodash);
+    assert_eq!(1, libraries.len());
+    assert_eq!("lodash", libraries[0].name);
+    assert_eq!("4.17.15", libraries[0].version);
+    assert!(libraries[0].outdated);
+
+    assert!(detect_libraries("app.js", b"var VERSION = '1.0.0';").is_empty());
+}
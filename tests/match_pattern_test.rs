@@ -0,0 +1,109 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{MatchPattern, MatchPatternError, XPI};
+
+#[test]
+fn test_parse_invalid_patterns() {
+    for (pattern, error) in [
+        ("example.com", MatchPatternError::MissingScheme),
+        (
+            "chrome://example.com/*",
+            MatchPatternError::UnsupportedScheme("chrome".to_owned()),
+        ),
+        ("https:///*", MatchPatternError::MissingHost),
+        (
+            "https://www.*.com/*",
+            MatchPatternError::InvalidHost("www.*.com".to_owned()),
+        ),
+        ("https://example.com", MatchPatternError::MissingPath),
+    ] {
+        assert_eq!(Err(error), MatchPattern::parse(pattern), "{}", pattern);
+    }
+}
+
+#[test]
+fn test_matches() {
+    for (pattern, url, expected) in [
+        ("<all_urls>", "https://example.com/", true),
+        ("<all_urls>", "file:///tmp/index.html", true),
+        ("<all_urls>", "about:blank", false),
+        ("*://*/*", "http://example.org/a/b", true),
+        ("*://*/*", "ftp://example.org/", false),
+        ("https://*.example.com/*", "https://example.com/", true),
+        ("https://*.example.com/*", "https://a.b.example.com/x", true),
+        ("https://*.example.com/*", "https://notexample.com/", false),
+        (
+            "https://example.com/foo*",
+            "https://example.com/foobar?q=1",
+            true,
+        ),
+        (
+            "https://example.com/foo*",
+            "https://example.com/bar#foo",
+            false,
+        ),
+        ("https://example.com/*", "http://example.com/", false),
+        ("http://localhost:3000/*", "http://localhost:3000/", true),
+        ("http://localhost:3000/*", "http://localhost:8080/", false),
+        ("file:///home/*", "file:///home/user/file.txt", true),
+    ] {
+        let parsed = MatchPattern::parse(pattern).unwrap();
+        assert_eq!(expected, parsed.matches(url), "{} {}", pattern, url);
+    }
+}
+
+#[test]
+fn test_match_url() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "permissions": ["storage", "https://*.example.com/*", "https://example.org/*"],
+      "content_scripts": [
+        { "matches": ["https://example.com/*"], "exclude_matches": ["https://example.com/admin/*"], "js": ["cs.js"] },
+        { "matches": ["<all_urls>"], "css": ["cs.css"] },
+        { "matches": ["not a pattern"], "js": ["other.js"] }
+      ],
+      "web_accessible_resources": [
+        { "resources": ["img.png"], "matches": ["https://example.com"] }
+      ]
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+
+    let matches = xpi.match_url("https://example.com/index.html").unwrap();
+    let scripts: Vec<(usize, &str)> = matches
+        .content_scripts
+        .iter()
+        .map(|script| (script.index, script.pattern.as_str()))
+        .collect();
+    assert_eq!(
+        vec![(0, "https://example.com/*"), (1, "<all_urls>")],
+        scripts
+    );
+    assert_eq!(1, matches.host_permissions.len());
+    assert_eq!(
+        "https://*.example.com/*",
+        matches.host_permissions[0].pattern
+    );
+
+    let matches = xpi.match_url("https://example.com/admin/").unwrap();
+    assert_eq!(1, matches.content_scripts.len());
+    assert_eq!(1, matches.content_scripts[0].index);
+
+    assert!(xpi.match_url("not a URL").is_none());
+
+    let invalid: Vec<(&str, &str)> = xpi
+        .permissions
+        .invalid_patterns
+        .iter()
+        .map(|diagnostic| (diagnostic.property.as_str(), diagnostic.pattern.as_str()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("content_scripts[2].matches", "not a pattern"),
+            ("web_accessible_resources[0].matches", "https://example.com"),
+        ],
+        invalid
+    );
+}
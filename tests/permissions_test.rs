@@ -0,0 +1,169 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{HostScope, PermissionSource, RiskLevel, XPI};
+
+#[test]
+fn test_mv2_host_permissions() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "permissions": ["storage", "tabs", "<all_urls>", "nativeMessaging"]
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+    let analysis = &xpi.permissions;
+
+    let api: Vec<(&str, RiskLevel)> = analysis
+        .api_permissions
+        .iter()
+        .map(|p| (p.name.as_str(), p.risk))
+        .collect();
+    assert_eq!(
+        vec![
+            ("storage", RiskLevel::Low),
+            ("tabs", RiskLevel::Medium),
+            ("nativeMessaging", RiskLevel::Critical),
+        ],
+        api
+    );
+    assert_eq!(1, analysis.host_permissions.len());
+    assert_eq!("<all_urls>", analysis.host_permissions[0].pattern);
+    assert_eq!(
+        PermissionSource::Permissions,
+        analysis.host_permissions[0].source
+    );
+    assert_eq!(HostScope::AllHosts, analysis.host_permissions[0].scope);
+    assert_eq!(Some(RiskLevel::Critical), analysis.risk());
+    assert_eq!(
+        vec![
+            "Access your data for all websites",
+            "Access browser tabs",
+            "Exchange messages with programs other than Firefox",
+        ],
+        analysis.warnings
+    );
+}
+
+#[test]
+fn test_mv3_host_permissions_and_content_scripts() {
+    let manifest = br#"{
+      "manifest_version": 3,
+      "name": "An extension",
+      "version": "1.0",
+      "permissions": ["storage"],
+      "host_permissions": ["https://example.com/*", "*://*.example.org/*"],
+      "content_scripts": [
+        { "matches": ["https://example.com/*", "https://example.net/path/*"], "js": ["cs.js"] }
+      ]
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+    let analysis = &xpi.permissions;
+
+    let hosts: Vec<(&str, PermissionSource, HostScope, RiskLevel)> = analysis
+        .host_permissions
+        .iter()
+        .map(|p| (p.pattern.as_str(), p.source, p.scope, p.risk))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                "https://example.com/*",
+                PermissionSource::HostPermissions,
+                HostScope::Host,
+                RiskLevel::Medium
+            ),
+            (
+                "*://*.example.org/*",
+                PermissionSource::HostPermissions,
+                HostScope::Domain,
+                RiskLevel::High
+            ),
+            (
+                "https://example.com/*",
+                PermissionSource::ContentScripts,
+                HostScope::Host,
+                RiskLevel::Medium
+            ),
+            (
+                "https://example.net/path/*",
+                PermissionSource::ContentScripts,
+                HostScope::Host,
+                RiskLevel::Medium
+            ),
+        ],
+        hosts
+    );
+    assert_eq!(Some(RiskLevel::High), analysis.risk());
+    assert_eq!(
+        vec![
+            "Access your data for example.com",
+            "Access your data for sites in the example.org domain",
+            "Access your data for example.net",
+        ],
+        analysis.warnings
+    );
+}
+
+#[test]
+fn test_misplaced_host_permissions() {
+    // Host permissions in `permissions` are ignored in Manifest V3.
+    let manifest = br#"{
+      "manifest_version": 3,
+      "name": "An extension",
+      "version": "1.0",
+      "permissions": ["storage", "<all_urls>"],
+      "host_permissions": ["https://example.com/*"]
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+    let analysis = &xpi.permissions;
+
+    assert_eq!(1, analysis.host_permissions.len());
+    assert_eq!(
+        "https://example.com/*",
+        analysis.host_permissions[0].pattern
+    );
+    assert_eq!(Some(RiskLevel::Medium), analysis.risk());
+    assert_eq!(1, analysis.ignored_host_permissions.len());
+    assert_eq!("permissions", analysis.ignored_host_permissions[0].property);
+    assert_eq!("<all_urls>", analysis.ignored_host_permissions[0].pattern);
+    assert!(!analysis
+        .warnings
+        .contains(&"Access your data for all websites".to_owned()));
+    assert!(format!("{}", analysis).contains(
+        "Ignored Host Permissions:\n   └── permissions: <all_urls> (host permissions must be \
+        declared in `host_permissions` in Manifest V3)"
+    ));
+
+    // `host_permissions` is ignored in Manifest V2.
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "permissions": ["https://example.com/*"],
+      "host_permissions": ["<all_urls>"]
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+    let analysis = &xpi.permissions;
+
+    assert_eq!(1, analysis.host_permissions.len());
+    assert_eq!(
+        PermissionSource::Permissions,
+        analysis.host_permissions[0].source
+    );
+    assert_eq!(Some(RiskLevel::Medium), analysis.risk());
+    assert_eq!(
+        "host_permissions",
+        analysis.ignored_host_permissions[0].property
+    );
+}
+
+#[test]
+fn test_no_permissions() {
+    let xpi = XPI::from_bytes(include_bytes!("fixtures/unsigned.zip")).unwrap();
+
+    assert!(xpi.permissions.api_permissions.is_empty());
+    assert!(xpi.permissions.host_permissions.is_empty());
+    assert!(xpi.permissions.warnings.is_empty());
+    assert_eq!(None, xpi.permissions.risk());
+}
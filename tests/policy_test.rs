@@ -0,0 +1,93 @@
+use std::io::Cursor;
+use std::time::Duration;
+use xpidump::{Policy, XPI};
+use zip::ZipArchive;
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn amo_info() -> XPI {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+    let reader = Cursor::new(bytes);
+    let mut archive = ZipArchive::new(reader).unwrap();
+
+    XPI::new(&mut archive)
+}
+
+fn rule_passed(report: &xpidump::PolicyReport, rule: &str) -> bool {
+    report
+        .results
+        .iter()
+        .find(|result| result.rule == rule)
+        .expect("expect rule result")
+        .passed
+}
+
+#[test]
+fn test_policy_passes() {
+    let xpi = amo_info();
+    let policy = Policy::from_json(
+        r#"{
+          "required_signatures": ["pkcs7", "cose"],
+          "allowed_kinds": ["regular"],
+          "allowed_environments": ["production"],
+          "allowed_pkcs7_algorithms": ["SHA-1", "SHA-256"],
+          "allowed_cose_algorithms": ["ES256"],
+          "allowed_ids": ["{db55bb9b-*}"],
+          "min_certificate_lifetime_days": 30
+        }"#,
+    )
+    .unwrap();
+
+    // The certificates of this XPI expire on 2025-04-04.
+    let report = policy.evaluate_at(&xpi, Duration::from_secs(1743724800 - 40 * DAY));
+
+    assert_eq!(7, report.results.len());
+    assert!(report.passed());
+}
+
+#[test]
+fn test_policy_fails() {
+    let xpi = amo_info();
+    let policy = Policy::from_json(
+        r#"{
+          "allowed_environments": ["staging", "development"],
+          "allowed_pkcs7_algorithms": ["SHA-256"],
+          "allowed_permissions": ["storage", "activeTab"],
+          "allowed_ids": ["*@mozilla.com"],
+          "min_certificate_lifetime_days": 30
+        }"#,
+    )
+    .unwrap();
+
+    let report = policy.evaluate_at(&xpi, Duration::from_secs(1743724800 - 10 * DAY));
+
+    assert!(!report.passed());
+    assert!(!rule_passed(&report, "allowed_environments"));
+    assert!(!rule_passed(&report, "allowed_pkcs7_algorithms"));
+    // The manifest contains a lot of host permissions.
+    assert!(!rule_passed(&report, "allowed_permissions"));
+    assert!(!rule_passed(&report, "allowed_ids"));
+    assert!(!rule_passed(&report, "min_certificate_lifetime_days"));
+}
+
+#[test]
+fn test_policy_unsigned_addon() {
+    let bytes = include_bytes!("fixtures/unsigned.zip");
+    let reader = Cursor::new(bytes);
+    let mut archive = ZipArchive::new(reader).unwrap();
+    let xpi = XPI::new(&mut archive);
+
+    let policy = Policy::from_json(
+        r#"{ "required_signatures": ["pkcs7"], "allowed_environments": ["production"] }"#,
+    )
+    .unwrap();
+    let report = policy.evaluate(&xpi);
+
+    assert!(!rule_passed(&report, "required_signatures"));
+    assert!(!rule_passed(&report, "allowed_environments"));
+}
+
+#[test]
+fn test_policy_rejects_unknown_rules() {
+    assert!(Policy::from_json(r#"{ "allowed_colors": ["red"] }"#).is_err());
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{ReferenceStatus, XPI};
+
+fn references(xpi: &XPI) -> Vec<(&str, &str, ReferenceStatus, Option<&str>)> {
+    xpi.references
+        .iter()
+        .map(|r| {
+            (
+                r.property.as_str(),
+                r.path.as_str(),
+                r.status,
+                r.actual_path.as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_references() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "An extension",
+      "version": "1.0",
+      "background": { "scripts": ["background.js", "lib/missing.js"] },
+      "content_scripts": [
+        { "matches": ["<all_urls>"], "js": ["./content/Script.js"], "css": ["content/style.css"] }
+      ],
+      "icons": { "48": "icons/icon-48.png" },
+      "browser_action": { "default_popup": "popup/popup.html?tab=1" },
+      "options_ui": { "page": "https://example.com/options.html" }
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[
+        ("manifest.json", manifest),
+        ("background.js", b""),
+        ("content/script.js", b""),
+        ("content/style.css", b""),
+        ("icons/icon-48.png", b""),
+        ("popup/popup.html", b""),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            (
+                "background.scripts[0]",
+                "background.js",
+                ReferenceStatus::Found,
+                None
+            ),
+            (
+                "background.scripts[1]",
+                "lib/missing.js",
+                ReferenceStatus::Missing,
+                None
+            ),
+            (
+                "content_scripts[0].js[0]",
+                "./content/Script.js",
+                ReferenceStatus::CaseMismatch,
+                Some("content/script.js")
+            ),
+            (
+                "content_scripts[0].css[0]",
+                "content/style.css",
+                ReferenceStatus::Found,
+                None
+            ),
+            (
+                "icons.48",
+                "icons/icon-48.png",
+                ReferenceStatus::Found,
+                None
+            ),
+            (
+                "browser_action.default_popup",
+                "popup/popup.html?tab=1",
+                ReferenceStatus::Found,
+                None
+            ),
+        ],
+        references(&xpi)
+    );
+
+    let output = format!("{}", xpi);
+    assert!(output.contains("REFERENCES:\n  4 of 6 referenced file(s) found"));
+    assert!(output.contains("[MISSING] background.scripts[1]: lib/missing.js"));
+    assert!(output.contains(
+        "[CASE MISMATCH] content_scripts[0].js[0]: ./content/Script.js (found content/script.js)"
+    ));
+}
+
+#[test]
+fn test_locales() {
+    let manifest = br#"{
+      "manifest_version": 2,
+      "name": "__MSG_name__",
+      "version": "1.0",
+      "default_locale": "en"
+    }"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[
+        ("manifest.json", manifest),
+        ("_locales/fr/messages.json", b"{}"),
+        ("_locales/de/README.md", b""),
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            (
+                "_locales.en",
+                "_locales/en/messages.json",
+                ReferenceStatus::Missing,
+                None
+            ),
+            (
+                "_locales.fr",
+                "_locales/fr/messages.json",
+                ReferenceStatus::Found,
+                None
+            ),
+            (
+                "_locales.de",
+                "_locales/de/messages.json",
+                ReferenceStatus::Missing,
+                None
+            ),
+        ],
+        references(&xpi)
+    );
+}
+
+#[test]
+fn test_no_manifest() {
+    let xpi = XPI::from_bytes(&make_xpi(&[("install.rdf", b"")])).unwrap();
+
+    assert!(xpi.references.is_empty());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{scan_source, FindingKind, ScanReport};
+
+fn kinds(source: &str) -> Vec<(usize, FindingKind)> {
+    scan_source("test.js", source)
+        .iter()
+        .map(|finding| (finding.line, finding.kind))
+        .collect()
+}
+
+#[test]
+fn test_risky_constructs() {
+    let source = r#"
+const value = eval(input);
+const evaluate = (x) => x; evaluate(1); obj.retrieval(2);
+const fn = new Function("a", "return a");
+el.innerHTML = html; el.innerHTML += more; if (el.innerHTML == "") {}
+document.write("<p>"); document.writeln("<p>");
+setTimeout("alert(1)", 10); setTimeout(() => {}, 10); setInterval('tick()', 5);
+"#;
+
+    assert_eq!(
+        vec![
+            (2, FindingKind::Eval),
+            (4, FindingKind::NewFunction),
+            (5, FindingKind::InnerHtmlAssignment),
+            (5, FindingKind::InnerHtmlAssignment),
+            (6, FindingKind::DocumentWrite),
+            (6, FindingKind::DocumentWrite),
+            (7, FindingKind::SetTimeoutString),
+            (7, FindingKind::SetTimeoutString),
+        ],
+        kinds(source)
+    );
+}
+
+#[test]
+fn test_remote_scripts() {
+    let source = r#"<script src="popup.js"></script>
+<script type="module" src="https://cdn.example.com/lib.js"></script>
+<SCRIPT SRC='//cdn.example.com/lib.js'></SCRIPT>"#;
+
+    assert_eq!(
+        vec![
+            (2, FindingKind::RemoteScript),
+            (3, FindingKind::RemoteScript)
+        ],
+        kinds(source)
+    );
+}
+
+#[test]
+fn test_obfuscation_indicators() {
+    let long_line = format!("var a = [{}];", "1,".repeat(600));
+    // A pseudo-random string using the base64 alphabet.
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut seed: u32 = 42;
+    let blob: String = (0..800)
+        .map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            alphabet[(seed >> 16) as usize % alphabet.len()] as char
+        })
+        .collect();
+    let blob = format!("var b = \"{}\";", blob);
+
+    assert_eq!(vec![(1, FindingKind::LongLine)], kinds(&long_line));
+    assert_eq!(vec![(1, FindingKind::HighEntropy)], kinds(&blob));
+}
+
+#[test]
+fn test_scan_xpi() {
+    let xpi = make_xpi(&[
+        ("manifest.json", br#"{ "eval": "eval(1)" }"#),
+        ("background.js", b"// nothing to see here\neval(code);\n"),
+        (
+            "popup/popup.html",
+            b"<script src=\"http://example.com/a.js\"></script>",
+        ),
+        ("README.md", b"eval(code)"),
+    ]);
+    let report = ScanReport::from_bytes(&xpi).unwrap();
+
+    let findings: Vec<(&str, usize, FindingKind)> = report
+        .findings
+        .iter()
+        .map(|finding| (finding.path.as_str(), finding.line, finding.kind))
+        .collect();
+    assert_eq!(
+        vec![
+            ("background.js", 2, FindingKind::Eval),
+            ("popup/popup.html", 1, FindingKind::RemoteScript),
+        ],
+        findings
+    );
+    assert_eq!("eval(code);", report.findings[0].snippet);
+}
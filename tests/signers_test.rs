@@ -0,0 +1,129 @@
+mod common;
+
+use cms::{
+    cert::x509::{
+        der::{asn1::OctetString, Any, Decode, Encode},
+        ext::pkix::SubjectKeyIdentifier,
+    },
+    content_info::ContentInfo,
+    signed_data::{SignedData, SignerIdentifier, SignerInfos},
+};
+use common::make_xpi;
+use std::io::{Cursor, Read};
+use xpidump::{DigestAlgorithm, Environment, SignatureAlgorithm, XPI};
+use zip::ZipArchive;
+
+/// Reads the PKCS#7 signature of a fixture.
+fn read_pkcs7(bytes: &[u8]) -> SignedData {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .by_name("META-INF/mozilla.rsa")
+        .unwrap()
+        .read_to_end(&mut buffer)
+        .unwrap();
+
+    let content_info = ContentInfo::from_der(&buffer).unwrap();
+    SignedData::from_der(&content_info.content.to_der().unwrap()).unwrap()
+}
+
+/// Builds an XPI file signed with the given PKCS#7 signature.
+fn make_signed_xpi(data: &SignedData) -> Vec<u8> {
+    let content_info = ContentInfo {
+        content_type: const_oid::db::rfc5911::ID_SIGNED_DATA,
+        content: Any::encode_from(data).unwrap(),
+    };
+    let signature = content_info.to_der().unwrap();
+
+    make_xpi(&[
+        ("manifest.json", br#"{ "manifest_version": 2 }"#),
+        ("META-INF/manifest.mf", b""),
+        ("META-INF/mozilla.sf", b""),
+        ("META-INF/mozilla.rsa", &signature),
+    ])
+}
+
+#[test]
+fn test_signer() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+    let pkcs7 = &xpi.signatures.pkcs7;
+
+    assert_eq!(1, pkcs7.signers.len());
+    let signer = &pkcs7.signers[0];
+    assert!(signer
+        .issuer
+        .as_deref()
+        .unwrap()
+        .contains("CN=signingca1.addons.mozilla.org"));
+    assert_eq!(
+        Some("17:68:86:1D:FC:D8:29:CE"),
+        signer.serial_number.as_deref()
+    );
+    assert_eq!(None, signer.subject_key_identifier);
+    assert_eq!(DigestAlgorithm::Sha1, signer.digest_algorithm);
+    assert_eq!(SignatureAlgorithm::RsaPkcs1Sha1, signer.signature_algorithm);
+    assert_eq!(
+        "2023-06-14T12:25:26Z",
+        signer.signing_time.as_ref().unwrap().to_string()
+    );
+    assert_eq!(Some("id-data"), signer.content_type.as_deref());
+    assert_eq!(
+        Some("ff18a583d6bb708d5734e951f5ef2f076fcdb3c7"),
+        signer.message_digest.as_deref()
+    );
+    assert_eq!(
+        "{db55bb9b-0d9f-407f-9b65-da9dd29c8d32}",
+        signer.certificate.as_ref().unwrap().common_name
+    );
+
+    assert!(format!("{}", xpi).contains("Signers:\n        └── Issuer"));
+    // COSE signatures do not have signers.
+    assert!(xpi.signatures.cose.signers.is_empty());
+}
+
+#[test]
+fn test_multiple_signers() {
+    let mut data = read_pkcs7(include_bytes!("fixtures/amo_info-1.25.0.xpi"));
+    let mut other = data.signer_infos.0.get(0).unwrap().clone();
+    other.sid = SignerIdentifier::SubjectKeyIdentifier(SubjectKeyIdentifier(
+        OctetString::new(vec![0xca, 0xfe]).unwrap(),
+    ));
+    other.digest_alg.oid = const_oid::db::rfc5912::ID_SHA_256;
+    other.signed_attrs = None;
+    let mut infos = data.signer_infos.0.into_vec();
+    infos.push(other);
+    data.signer_infos = SignerInfos(infos.try_into().unwrap());
+
+    let xpi = XPI::from_bytes(&make_signed_xpi(&data)).unwrap();
+    let pkcs7 = &xpi.signatures.pkcs7;
+
+    assert_eq!(2, pkcs7.signers.len());
+    let signer = pkcs7
+        .signers
+        .iter()
+        .find(|signer| signer.subject_key_identifier.is_some())
+        .unwrap();
+    assert_eq!(Some("cafe"), signer.subject_key_identifier.as_deref());
+    assert_eq!(DigestAlgorithm::Sha256, signer.digest_algorithm);
+    assert_eq!(None, signer.signing_time);
+    assert_eq!(None, signer.content_type);
+    // There is no certificate with this subject key identifier.
+    assert!(signer.certificate.is_none());
+}
+
+#[test]
+fn test_no_signers() {
+    let mut data = read_pkcs7(include_bytes!("fixtures/amo_info-1.25.0.xpi"));
+    data.signer_infos = SignerInfos(vec![].try_into().unwrap());
+    data.certificates = None;
+
+    let xpi = XPI::from_bytes(&make_signed_xpi(&data)).unwrap();
+    let pkcs7 = &xpi.signatures.pkcs7;
+
+    assert!(pkcs7.exists());
+    assert!(pkcs7.signers.is_empty());
+    assert!(pkcs7.certificates.is_empty());
+    assert_eq!(None, pkcs7.algorithm);
+    assert_eq!(Environment::Unknown, pkcs7.env());
+    assert!(format!("{}", xpi).contains("PRESENT / UNKNOWN / N/A / REGULAR ADD-ON"));
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use cms::{
+    cert::x509::der::{Decode, Encode},
+    content_info::ContentInfo,
+    signed_data::SignedData,
+};
+use common::make_xpi;
+use minicbor::{data::Tag, Decoder, Encoder};
+use p256::ecdsa::{signature::Verifier, DerSignature, Signature, VerifyingKey};
+use std::io::{Cursor, Read};
+use xpidump::{
+    Algorithm, CoseQuirk, DigestAlgorithm, SignatureAlgorithm, SigningCredentials, SigningError,
+    VerdictStatus, XPI,
+};
+use zip::ZipArchive;
+
+const KEY: &str = include_str!("fixtures/signing/key.pem");
+const CHAIN: &str = include_str!("fixtures/signing/chain.pem");
+
+fn read_file(bytes: &[u8], name: &str) -> Vec<u8> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_end(&mut buffer)
+        .unwrap();
+
+    buffer
+}
+
+fn verifying_key() -> VerifyingKey {
+    let chain = cms::cert::x509::Certificate::load_pem_chain(CHAIN.as_bytes()).unwrap();
+    VerifyingKey::from_sec1_bytes(
+        chain[0]
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_sign() {
+    let long_name = format!("{}.js", "a".repeat(100));
+    let bytes = make_xpi(&[
+        ("manifest.json", br#"{ "manifest_version": 2 }"#),
+        ("content/script.js", b"console.log('hello');"),
+        (&long_name, b""),
+    ]);
+    let credentials = SigningCredentials::from_pem(KEY, CHAIN).unwrap();
+    let signed = credentials.sign(&bytes).unwrap();
+    let xpi = XPI::from_bytes(&signed).unwrap();
+
+    let verdict = xpi.verdict();
+    assert_eq!(VerdictStatus::SignedUntrustedRoot, verdict.status);
+    assert_eq!(Some("xpidump test root"), verdict.root.as_deref());
+    assert!(xpi.signatures.mismatches.is_empty());
+
+    for signature in [&xpi.signatures.pkcs7, &xpi.signatures.cose] {
+        assert_eq!(
+            vec!["xpidump test intermediate", "test@xpidump"],
+            signature
+                .certificates
+                .iter()
+                .map(|cert| cert.common_name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(
+        Some(Algorithm::Digest(DigestAlgorithm::Sha256)),
+        xpi.signatures.pkcs7.algorithm
+    );
+    assert_eq!(
+        SignatureAlgorithm::EcdsaSha256,
+        xpi.signatures.pkcs7.signers[0].signature_algorithm
+    );
+    assert_eq!(
+        Some(Algorithm::Signature(SignatureAlgorithm::EcdsaSha256)),
+        xpi.signatures.cose.algorithm
+    );
+    assert_eq!(
+        vec![CoseQuirk::KidArray, CoseQuirk::KidCertificate],
+        xpi.signatures.cose.quirks
+    );
+
+    // Long names are split into continuation lines.
+    let manifest = String::from_utf8(read_file(&signed, "META-INF/manifest.mf")).unwrap();
+    assert!(manifest.contains(&format!(
+        "Name: {}\n {}\n",
+        &long_name[..66],
+        &long_name[66..]
+    )));
+    assert!(manifest.contains("Name: META-INF/cose.sig\n"));
+}
+
+#[test]
+fn test_signatures_verify() {
+    let bytes = make_xpi(&[("manifest.json", br#"{ "manifest_version": 2 }"#)]);
+    let signed = SigningCredentials::from_pem(KEY, CHAIN)
+        .unwrap()
+        .sign(&bytes)
+        .unwrap();
+    let key = verifying_key();
+
+    // PKCS#7: the signature covers the signed attributes.
+    let content_info = ContentInfo::from_der(&read_file(&signed, "META-INF/mozilla.rsa")).unwrap();
+    let data = SignedData::from_der(&content_info.content.to_der().unwrap()).unwrap();
+    let signer = data.signer_infos.0.get(0).unwrap();
+    let signature = DerSignature::from_bytes(signer.signature.as_bytes()).unwrap();
+    key.verify(
+        &signer.signed_attrs.as_ref().unwrap().to_der().unwrap(),
+        &signature,
+    )
+    .unwrap();
+
+    // COSE: the signature covers the `Sig_structure` with the COSE manifest as payload.
+    let cose = read_file(&signed, "META-INF/cose.sig");
+    let mut decoder = Decoder::new(&cose);
+    decoder.tag().unwrap();
+    decoder.array().unwrap();
+    let protected = decoder.bytes().unwrap();
+    decoder.skip().unwrap();
+    decoder.skip().unwrap();
+    decoder.array().unwrap();
+    decoder.array().unwrap();
+    let signer_protected = decoder.bytes().unwrap();
+    decoder.skip().unwrap();
+    let signature = Signature::from_slice(decoder.bytes().unwrap()).unwrap();
+
+    let mut to_be_signed = Encoder::new(Vec::new());
+    to_be_signed
+        .array(5)
+        .unwrap()
+        .str("Signature")
+        .unwrap()
+        .bytes(protected)
+        .unwrap()
+        .bytes(signer_protected)
+        .unwrap()
+        .bytes(&[])
+        .unwrap()
+        .bytes(&read_file(&signed, "META-INF/cose.manifest"))
+        .unwrap();
+    key.verify(to_be_signed.writer(), &signature).unwrap();
+    assert_eq!(Tag::new(98), Decoder::new(&cose).tag().unwrap());
+}
+
+#[test]
+fn test_resign() {
+    let credentials = SigningCredentials::from_pem(KEY, CHAIN).unwrap();
+    let signed = credentials
+        .sign(include_bytes!("fixtures/amo_info-1.25.0.xpi"))
+        .unwrap();
+    let xpi = XPI::from_bytes(&signed).unwrap();
+
+    // The signature files are replaced.
+    assert_eq!(VerdictStatus::SignedUntrustedRoot, xpi.verdict().status);
+    assert_eq!(
+        "test@xpidump",
+        xpi.signatures.pkcs7.end_entity().unwrap().common_name
+    );
+    assert_eq!(Some("1.25.0"), xpi.manifest.version.as_deref());
+}
+
+#[test]
+fn test_invalid_credentials() {
+    assert!(matches!(
+        SigningCredentials::from_pem("invalid", CHAIN),
+        Err(SigningError::InvalidKey)
+    ));
+    assert!(matches!(
+        SigningCredentials::from_pem(KEY, ""),
+        Err(SigningError::InvalidCertificates)
+    ));
+
+    // The chain must start with the end-entity certificate.
+    let intermediate = &CHAIN[CHAIN.find("-----END CERTIFICATE-----").unwrap() + 26..];
+    assert!(matches!(
+        SigningCredentials::from_pem(KEY, intermediate),
+        Err(SigningError::KeyMismatch)
+    ));
+}
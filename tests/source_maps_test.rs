@@ -0,0 +1,113 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{Bundler, ScanReport, SourceMapLocation, XPI};
+
+#[test]
+fn test_source_maps() {
+    let xpi = make_xpi(&[
+        (
+            "js/app.js",
+            b"(() => { // webpackBootstrap\n})();\n//# sourceMappingURL=app.js.map\n",
+        ),
+        (
+            "js/app.js.map",
+            br#"{"version":3,"file":"app.js","sourceRoot":"","sources":["webpack:///./src/app.js","webpack:///./src/utils.js"],"sourcesContent":["",""],"mappings":""}"#,
+        ),
+        (
+            "js/lib.js",
+            b"var x = commonjsGlobal;\n//# sourceMappingURL=https://example.com/lib.js.map",
+        ),
+        (
+            "css/style.css",
+            b"body {}\n/*# sourceMappingURL=../maps/style.css.map */",
+        ),
+        (
+            "js/inline.js",
+            b"var s = '//# sourceMappingURL=not-a-comment.map';\n//# sourceMappingURL=data:application/json;base64,e30=",
+        ),
+    ]);
+    let report = ScanReport::from_bytes(&xpi).unwrap();
+
+    let urls: Vec<(&str, usize, SourceMapLocation, Option<&str>)> = report
+        .source_mapping_urls
+        .iter()
+        .map(|url| {
+            (
+                url.path.as_str(),
+                url.line,
+                url.location,
+                url.resolved_path.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                "js/app.js",
+                3,
+                SourceMapLocation::Found,
+                Some("js/app.js.map")
+            ),
+            ("js/lib.js", 2, SourceMapLocation::Remote, None),
+            (
+                "css/style.css",
+                2,
+                SourceMapLocation::Missing,
+                Some("maps/style.css.map")
+            ),
+            ("js/inline.js", 2, SourceMapLocation::Inline, None),
+        ],
+        urls
+    );
+
+    assert_eq!(1, report.source_maps.len());
+    let source_map = &report.source_maps[0];
+    assert_eq!("js/app.js.map", source_map.path);
+    assert_eq!(Some("app.js".to_owned()), source_map.file);
+    assert_eq!(
+        vec!["webpack:///./src/app.js", "webpack:///./src/utils.js"],
+        source_map.sources
+    );
+    assert!(source_map.has_sources_content);
+
+    // The source maps are also flagged in the inventory of the files.
+    let entries = XPI::from_bytes(&xpi).unwrap().entries;
+    assert_eq!(
+        vec!["js/app.js.map"],
+        entries
+            .iter()
+            .filter(|entry| entry.is_source_map)
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    let bundles: Vec<(&str, Bundler)> = report
+        .bundles
+        .iter()
+        .map(|bundle| (bundle.path.as_str(), bundle.bundler))
+        .collect();
+    assert_eq!(
+        vec![
+            ("js/app.js", Bundler::Webpack),
+            ("js/lib.js", Bundler::Rollup)
+        ],
+        bundles
+    );
+}
+
+#[test]
+fn test_webpack_fixture() {
+    let report =
+        ScanReport::from_bytes(include_bytes!("fixtures/dev-old-recommended.xpi")).unwrap();
+
+    assert_eq!(4, report.source_maps.len());
+    assert!(report
+        .source_maps
+        .iter()
+        .all(|source_map| source_map.sources[0] == "webpack:///webpack/bootstrap"));
+    assert!(report
+        .bundles
+        .iter()
+        .all(|bundle| bundle.bundler == Bundler::Webpack));
+}
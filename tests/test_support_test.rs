@@ -0,0 +1,58 @@
+mod common;
+
+use common::make_xpi;
+use xpidump::{test_support::CertificateHierarchy, Environment, SignatureKind, VerdictStatus, XPI};
+
+#[test]
+fn test_all_hierarchies() {
+    let bytes = make_xpi(&[("manifest.json", br#"{ "manifest_version": 2 }"#)]);
+    let hierarchies = CertificateHierarchy::all("test@example.com");
+    assert_eq!(12, hierarchies.len());
+
+    for hierarchy in hierarchies {
+        let xpi = XPI::from_bytes(&hierarchy.sign(&bytes).unwrap()).unwrap();
+        // The test roots are not AMO roots, whatever the environment.
+        assert_eq!(VerdictStatus::SignedUntrustedRoot, xpi.verdict().status);
+        assert!(xpi.signatures.mismatches.is_empty());
+
+        for signature in [&xpi.signatures.pkcs7, &xpi.signatures.cose] {
+            assert_eq!(hierarchy.kind, signature.kind());
+            assert_eq!(
+                "test@example.com",
+                signature.end_entity().unwrap().common_name
+            );
+            // Unknown certificates are reported as production certificates.
+            if hierarchy.environment != Environment::Unknown {
+                assert_eq!(hierarchy.environment, signature.env());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_development_hierarchy() {
+    let hierarchy = CertificateHierarchy::new(
+        Environment::Development,
+        SignatureKind::Privileged,
+        "test@example.com",
+    );
+
+    // The root certificate signs the end-entity certificate and is embedded in the signatures.
+    assert!(hierarchy.intermediate.is_none());
+    assert_eq!(
+        hierarchy.root.tbs_certificate.subject,
+        hierarchy.end_entity.tbs_certificate.issuer
+    );
+    assert_eq!(
+        vec![hierarchy.end_entity.clone(), hierarchy.root.clone()],
+        hierarchy.chain()
+    );
+
+    // The hierarchies are reproducible.
+    let other = CertificateHierarchy::new(
+        Environment::Development,
+        SignatureKind::Privileged,
+        "test@example.com",
+    );
+    assert_eq!(hierarchy.end_entity, other.end_entity);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::make_xpi;
+use std::io::{Cursor, Read};
+use xpidump::{unsign, VerdictStatus, XPI};
+use zip::ZipArchive;
+
+/// Returns the name, compression method, CRC-32 and compressed content of each entry.
+fn raw_entries(bytes: &[u8]) -> Vec<(String, String, u32, Vec<u8>)> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    (0..archive.len())
+        .map(|index| {
+            let mut file = archive.by_index_raw(index).unwrap();
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).unwrap();
+            (
+                file.name().to_owned(),
+                file.compression().to_string(),
+                file.crc32(),
+                buffer,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_unsign() {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+    let unsigned = unsign(bytes).unwrap();
+
+    assert_eq!(
+        vec![
+            "META-INF/cose.manifest",
+            "META-INF/cose.sig",
+            "META-INF/manifest.mf",
+            "META-INF/mozilla.sf",
+            "META-INF/mozilla.rsa",
+        ],
+        unsigned.removed_files
+    );
+
+    let xpi = XPI::from_bytes(&unsigned.bytes).unwrap();
+    assert!(!xpi.signatures.has_signatures());
+    assert_eq!(VerdictStatus::Unsigned, xpi.verdict().status);
+    assert_eq!(Some("1.25.0"), xpi.manifest.version.as_deref());
+
+    // The other entries are kept byte-for-byte.
+    let expected: Vec<_> = raw_entries(bytes)
+        .into_iter()
+        .filter(|(name, ..)| !name.starts_with("META-INF/"))
+        .collect();
+    assert_eq!(expected, raw_entries(&unsigned.bytes));
+
+    assert!(format!("{}", unsigned).starts_with("REMOVED FILES:\n  META-INF/cose.manifest\n"));
+}
+
+#[test]
+fn test_unsign_unsigned() {
+    let bytes = make_xpi(&[
+        ("manifest.json", br#"{ "manifest_version": 2 }"#),
+        // Only the signature files are removed from `META-INF/`.
+        ("META-INF/notes.txt", b"not a signature"),
+    ]);
+    let unsigned = unsign(&bytes).unwrap();
+
+    assert!(unsigned.removed_files.is_empty());
+    assert_eq!(raw_entries(&bytes), raw_entries(&unsigned.bytes));
+    assert_eq!("REMOVED FILES:\n  NONE", format!("{}", unsigned));
+}
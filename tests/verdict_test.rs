@@ -0,0 +1,293 @@
+mod common;
+
+use cms::{
+    cert::x509::der::{Any, Decode, Encode},
+    cert::CertificateChoices,
+    content_info::ContentInfo,
+    signed_data::{CertificateSet, SignedData},
+};
+use common::{make_xpi, patch_fixture, remove_files};
+use minicbor::{data::Tag, Encoder};
+use std::io::{Cursor, Read};
+use xpidump::{VerdictStatus, XPI};
+use zip::ZipArchive;
+
+const PKCS7_FILES: [&str; 3] = [
+    "META-INF/manifest.mf",
+    "META-INF/mozilla.sf",
+    "META-INF/mozilla.rsa",
+];
+
+/// Returns the PKCS#7 signature of a fixture and its certificates issued by `issuer`.
+fn read_pkcs7(bytes: &[u8], issuer: &str) -> (ContentInfo, SignedData, Vec<CertificateChoices>) {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .by_name("META-INF/mozilla.rsa")
+        .unwrap()
+        .read_to_end(&mut buffer)
+        .unwrap();
+
+    let content_info = ContentInfo::from_der(&buffer).unwrap();
+    let data = SignedData::from_der(&content_info.content.to_der().unwrap()).unwrap();
+    let certificates = data
+        .certificates
+        .clone()
+        .unwrap()
+        .0
+        .into_vec()
+        .into_iter()
+        .filter(|choice| match choice {
+            CertificateChoices::Certificate(cert) => cert
+                .tbs_certificate
+                .issuer
+                .to_string()
+                .contains(&format!("CN={}", issuer)),
+            _ => false,
+        })
+        .collect();
+
+    (content_info, data, certificates)
+}
+
+#[test]
+fn test_signed() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::ClaimsProductionRoot, verdict.status);
+    assert!(verdict.is_signed());
+    assert_eq!(Some("root-ca-production-amo"), verdict.root.as_deref());
+    assert!(!verdict.privileged && !verdict.system && !verdict.enterprise && !verdict.recommended);
+    assert_eq!(
+        vec![
+            "the COSE certificate chain claims the production root certificate \
+             (root-ca-production-amo), which is not cryptographically verified"
+        ],
+        verdict.reasons
+    );
+
+    let json = serde_json::to_value(&xpi).unwrap();
+    assert_eq!("claims_production_root", json["verdict"]["status"]);
+    assert!(format!("{}", xpi).contains("VERDICT:\n  CLAIMS PRODUCTION ROOT (NOT VERIFIED)\n"));
+}
+
+#[test]
+fn test_flags() {
+    let verdict = |path: &str| {
+        let xpi = XPI::from_path(path).unwrap();
+        let verdict = xpi.verdict();
+        (
+            verdict.status,
+            verdict.privileged,
+            verdict.system,
+            verdict.enterprise,
+            verdict.recommended,
+        )
+    };
+
+    assert_eq!(
+        (
+            VerdictStatus::ClaimsProductionRoot,
+            true,
+            false,
+            false,
+            false
+        ),
+        verdict("tests/fixtures/remote-settings-devtools.xpi")
+    );
+    assert_eq!(
+        (VerdictStatus::ClaimsStagingRoot, false, true, false, false),
+        verdict("tests/fixtures/webcompat.xpi")
+    );
+    assert_eq!(
+        (VerdictStatus::ClaimsStagingRoot, false, false, true, false),
+        verdict("tests/fixtures/enterprise-dev.xpi")
+    );
+    assert_eq!(
+        (VerdictStatus::ClaimsStagingRoot, false, false, false, true),
+        verdict("tests/fixtures/dev-old-recommended.xpi")
+    );
+    assert_eq!(
+        (
+            VerdictStatus::ClaimsDevelopmentRoot,
+            false,
+            false,
+            false,
+            false
+        ),
+        verdict("tests/fixtures/amo-localdev.xpi")
+    );
+}
+
+#[test]
+fn test_unsigned() {
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", b"{}")])).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::Unsigned, verdict.status);
+    assert!(!verdict.is_signed());
+    assert_eq!(vec!["no signature found"], verdict.reasons);
+    assert!(format!("{}", xpi).contains("VERDICT:\n  UNSIGNED\n   └── no signature found"));
+}
+
+#[test]
+fn test_tampered() {
+    let bytes = patch_fixture(
+        include_bytes!("fixtures/amo_info-1.25.0.xpi"),
+        &[
+            ("background.js", b"alert('hello');"),
+            ("extra.js", b"alert('hello');"),
+        ],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::Tampered, verdict.status);
+    assert_eq!(None, verdict.root);
+    assert_eq!(
+        vec![
+            "background.js does not match its digest in META-INF/manifest.mf",
+            "extra.js is not listed in META-INF/manifest.mf",
+            "background.js does not match its digest in META-INF/cose.manifest",
+            "extra.js is not listed in META-INF/cose.manifest",
+        ],
+        verdict.reasons
+    );
+}
+
+#[test]
+fn test_tampered_signed_manifest() {
+    let bytes = patch_fixture(
+        include_bytes!("fixtures/colorzilla-3.3.xpi"),
+        &[("META-INF/manifest.mf", b"Manifest-Version: 1.0\n")],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::Tampered, verdict.status);
+    assert!(verdict
+        .reasons
+        .contains(&"manifest.json is not listed in META-INF/manifest.mf".to_owned()));
+    assert!(verdict.reasons.contains(
+        &"the digest of META-INF/manifest.mf does not match META-INF/mozilla.sf".to_owned()
+    ));
+}
+
+#[test]
+fn test_cose_only() {
+    let bytes = remove_files(include_bytes!("fixtures/amo_info-1.25.0.xpi"), &PKCS7_FILES);
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::ClaimsProductionRoot, verdict.status);
+    assert_eq!(
+        "the PKCS#7 signature is missing, only the COSE signature is present",
+        verdict.reasons[0]
+    );
+}
+
+#[test]
+fn test_incomplete_signature() {
+    let bytes = remove_files(
+        include_bytes!("fixtures/amo_info-1.25.0.xpi"),
+        &["META-INF/mozilla.rsa"],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::Tampered, verdict.status);
+    assert_eq!(
+        vec!["the PKCS#7 signature files are incomplete"],
+        verdict.reasons
+    );
+}
+
+#[test]
+fn test_pkcs7_root_mismatch() {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+
+    // Remove the intermediate certificate so that the PKCS#7 chain ends with an unknown
+    // certificate, the COSE chain is left as is.
+    let (mut content_info, mut data, certificates) =
+        read_pkcs7(bytes, "signingca1.addons.mozilla.org");
+    assert_eq!(1, certificates.len());
+    data.certificates = Some(CertificateSet(certificates.try_into().unwrap()));
+    content_info.content = Any::encode_from(&data).unwrap();
+
+    let bytes = patch_fixture(
+        bytes,
+        &[("META-INF/mozilla.rsa", &content_info.to_der().unwrap())],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    // The COSE signature is evaluated.
+    assert_eq!(VerdictStatus::ClaimsProductionRoot, verdict.status);
+    assert_eq!(Some("root-ca-production-amo"), verdict.root.as_deref());
+    assert_eq!(
+        "the PKCS#7 certificate chain names another root certificate \
+         (signingca1.addons.mozilla.org)",
+        verdict.reasons[1]
+    );
+}
+
+#[test]
+fn test_untrusted_root() {
+    let bytes = include_bytes!("fixtures/amo_info-1.25.0.xpi");
+    let (_, _, certificates) = read_pkcs7(bytes, "signingca1.addons.mozilla.org");
+    let CertificateChoices::Certificate(end_entity) = &certificates[0] else {
+        panic!("expected a certificate");
+    };
+
+    // A COSE signature with the end-entity certificate only, so that the chain ends with an
+    // unknown certificate.
+    let mut encoder = Encoder::new(vec![]);
+    encoder
+        .tag(Tag::new(98))
+        .unwrap()
+        .array(4)
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .map(0)
+        .unwrap()
+        .null()
+        .unwrap()
+        .array(1)
+        .unwrap()
+        .array(3)
+        .unwrap()
+        .bytes(b"")
+        .unwrap()
+        .map(1)
+        .unwrap()
+        .u8(4)
+        .unwrap()
+        .bytes(&end_entity.to_der().unwrap())
+        .unwrap()
+        .bytes(b"signature")
+        .unwrap();
+
+    let bytes = patch_fixture(
+        &remove_files(bytes, &PKCS7_FILES),
+        &[("META-INF/cose.sig", encoder.writer())],
+    );
+    let xpi = XPI::from_bytes(&bytes).unwrap();
+    let verdict = xpi.verdict();
+
+    assert_eq!(VerdictStatus::SignedUntrustedRoot, verdict.status);
+    assert!(verdict.is_signed());
+    assert_eq!(
+        Some("signingca1.addons.mozilla.org"),
+        verdict.root.as_deref()
+    );
+    assert_eq!(
+        vec![
+            "the PKCS#7 signature is missing, only the COSE signature is present",
+            "the root certificate (signingca1.addons.mozilla.org) of the COSE certificate chain \
+             is not trusted"
+        ],
+        verdict.reasons
+    );
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::make_xpi;
+use serde_json::Value;
+use xpidump::XPI;
+
+/// The WASM bindings, which declare the TypeScript definitions of the objects returned to JS.
+const BINDINGS: &str = include_str!("../src/wasm_bindings.rs");
+
+/// Returns the names of the fields declared in a TypeScript interface, sorted. The inherited
+/// fields are not included.
+fn interface_fields(name: &str) -> Vec<String> {
+    let start = BINDINGS
+        .find(&format!("export interface {} ", name))
+        .unwrap_or_else(|| panic!("expect interface {}", name));
+    let body = &BINDINGS[start..];
+    let body = &body[body.find('{').unwrap() + 1..body.find('}').unwrap()];
+
+    let mut fields: Vec<String> = body
+        .split(';')
+        .filter_map(|field| field.split(':').next())
+        .map(|name| name.trim().trim_end_matches('?').to_owned())
+        .filter(|name| !name.is_empty())
+        .collect();
+    fields.sort();
+    fields
+}
+
+/// Returns the string literals of a TypeScript union type.
+fn type_literals(name: &str) -> Vec<String> {
+    let start = BINDINGS
+        .find(&format!("export type {} =", name))
+        .unwrap_or_else(|| panic!("expect type {}", name));
+    let body = &BINDINGS[start..];
+    let body = &body[body.find('=').unwrap() + 1..body.find(';').unwrap()];
+
+    body.split('|')
+        .map(|literal| literal.trim().trim_matches('"').to_owned())
+        .filter(|literal| !literal.is_empty())
+        .collect()
+}
+
+/// Returns the keys of a serialized object, sorted.
+fn keys(value: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = value
+        .as_object()
+        .expect("expect object")
+        .keys()
+        .cloned()
+        .collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn test_interfaces() {
+    let xpi = XPI::from_path("tests/fixtures/amo_info-1.25.0.xpi").unwrap();
+    let value = serde_json::to_value(&xpi).unwrap();
+
+    for (name, value) in [
+        ("XPIData", &value),
+        ("Manifest", &value["manifest"]),
+        ("PermissionAnalysis", &value["permissions"]),
+        ("ApiPermission", &value["permissions"]["api_permissions"][0]),
+        (
+            "HostPermission",
+            &value["permissions"]["host_permissions"][0],
+        ),
+        ("Reference", &value["references"][0]),
+        ("Icon", &value["icons"][0]),
+        ("Signatures", &value["signatures"]),
+        ("Signature", &value["signatures"]["pkcs7"]),
+        ("Signer", &value["signatures"]["pkcs7"]["signers"][0]),
+        (
+            "Certificate",
+            &value["signatures"]["cose"]["certificates"][0],
+        ),
+        ("Verdict", &value["verdict"]),
+        ("Entry", &value["entries"][0]),
+    ] {
+        assert_eq!(interface_fields(name), keys(value), "{}", name);
+    }
+    assert!(type_literals("AddonFormat").contains(&"web_extension".to_owned()));
+}
+
+#[test]
+fn test_addon_types() {
+    let theme = br##"{ "manifest_version": 2, "theme": { "colors": { "frame": "#adb09f" } } }"##;
+    let dictionary = br#"{ "manifest_version": 2, "dictionaries": { "fr": "fr.dic" } }"#;
+    let language_pack = br#"{ "manifest_version": 2, "langpack_id": "fr", "languages": {} }"#;
+    let site_permission = br#"{ "manifest_version": 2, "site_permissions": ["midi"] }"#;
+
+    for (manifest, addon_type, details) in [
+        (&theme[..], "theme", "ThemeDetails"),
+        (&dictionary[..], "dictionary", "DictionaryDetails"),
+        (&language_pack[..], "language_pack", "LanguagePackDetails"),
+        (
+            &site_permission[..],
+            "site_permission",
+            "SitePermissionDetails",
+        ),
+    ] {
+        let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", manifest)])).unwrap();
+        let mut value = serde_json::to_value(&xpi.addon_type).unwrap();
+
+        // The details are flattened in the object, next to the `type` tag.
+        let tag = value.as_object_mut().unwrap().remove("type").unwrap();
+        assert_eq!(addon_type, tag);
+        assert_eq!(interface_fields(details), keys(&value), "{}", details);
+    }
+
+    let xpi = XPI::from_bytes(&make_xpi(&[("manifest.json", dictionary)])).unwrap();
+    let value = serde_json::to_value(&xpi.addon_type).unwrap();
+    assert_eq!(
+        interface_fields("Dictionary"),
+        keys(&value["dictionaries"][0])
+    );
+}
+
+#[test]
+fn test_legacy_manifest() {
+    let install_rdf = br#"<RDF xmlns:em="http://www.mozilla.org/2004/em-rdf#"><Description>
+      <em:bootstrap>true</em:bootstrap>
+      <em:targetApplication><Description em:id="firefox" /></em:targetApplication>
+    </Description></RDF>"#;
+    let xpi = XPI::from_bytes(&make_xpi(&[("install.rdf", install_rdf)])).unwrap();
+    let value = serde_json::to_value(&xpi).unwrap();
+
+    let legacy = &value["legacy_manifest"];
+    assert_eq!(interface_fields("LegacyManifest"), keys(legacy));
+    assert_eq!(
+        interface_fields("TargetApplication"),
+        keys(&legacy["target_applications"][0])
+    );
+    assert!(type_literals("AddonFormat").contains(&"bootstrapped".to_owned()));
+    assert_eq!("bootstrapped", value["format"]);
+}
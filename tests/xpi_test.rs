@@ -9,7 +9,11 @@ fn assert_signature(signature: &Signature, kind: SignatureKind, env: Environment
     assert_eq!(env, signature.env());
     assert_eq!(
         algorithm,
-        signature.algorithm.as_ref().expect("expect algorithm")
+        signature
+            .algorithm
+            .as_ref()
+            .expect("expect algorithm")
+            .to_string()
     );
 
     let expected_ou = match kind {
@@ -369,3 +373,29 @@ fn test_enterprise() {
     assert!(xpi.manifest.exists());
     assert!(xpi.is_enterprise());
 }
+
+#[test]
+fn test_from_bytes() {
+    let xpi = XPI::from_bytes(include_bytes!("fixtures/dev-new.xpi")).unwrap();
+
+    assert!(xpi.manifest.exists());
+    assert_eq!("16.0", xpi.manifest.version.expect("expect add-on version"));
+    assert!(xpi.signatures.has_signatures());
+
+    assert!(matches!(
+        XPI::from_bytes(b"not a zip file"),
+        Err(xpidump::Error::Zip(_))
+    ));
+}
+
+#[test]
+fn test_from_path() {
+    let xpi = XPI::from_path("tests/fixtures/enterprise-dev.xpi").unwrap();
+
+    assert!(xpi.is_enterprise());
+
+    assert!(matches!(
+        XPI::from_path("tests/fixtures/does-not-exist.xpi"),
+        Err(xpidump::Error::Io(_))
+    ));
+}
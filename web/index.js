@@ -12,31 +12,53 @@ const updateUI = (xpi) => {
 
   const {
     cose_algorithm,
-    env,
     has_cose_sig,
-    has_pkcs7_sig,
-    is_enterprise,
     kind,
     manifest,
     pkcs7_algorithm,
+    verdict,
   } = xpi;
 
   // We don't know what kind of add-on we are looking at when it is not signed.
-  const prettyKind = has_pkcs7_sig
+  const prettyKind = !["unsigned", "tampered"].includes(verdict.status)
     ? `<strong>${kind}</strong> add-on`
     : "add-on";
 
+  let prettySignature;
+  switch (verdict.status) {
+    case "unsigned":
+      prettySignature = `❌ It doesn't appear to be signed.`;
+      break;
+    case "tampered":
+      prettySignature = `⚠️ Its content does <strong>not</strong> match its signature.`;
+      break;
+    case "signed_untrusted_root":
+      prettySignature = `⚠️ Its certificate chain names an <strong>untrusted</strong> root certificate (<code>${verdict.root}</code>).`;
+      break;
+    default:
+      prettySignature = `${has_cose_sig ? "🔐" : "🔓"} Its certificate chain <strong>claims</strong> the <strong>${verdict.status.replace(/^claims_(.*)_root$/, "$1")}</strong> root certificate (<code>${verdict.root}</code>), nothing is cryptographically verified. ${has_cose_sig ? "This add-on is dual-signed (PKCS#7 and COSE)" : "This add-on is <strong>not</strong> signed with COSE"}. The PKCS#7 digest algorithm is: <strong>${pkcs7_algorithm}</strong>. ${has_cose_sig ? `The COSE algorithm is: <strong>${cose_algorithm}</strong>.` : ""}`;
+  }
+
   $outputPretty.innerHTML = `
     ✅ ${manifest.id ? `This ${prettyKind} has the following ID in its manifest: <code>${manifest.id}</code>` : `This ${prettyKind} does not have an ID in its manifest`}. Its version is: <code>${manifest.version}</code>.
     <br>
     <br>
-    ${has_pkcs7_sig ? `${has_cose_sig ? "🔐" : "🔓"} It has been signed with the <strong>${env}</strong> root certificate. ${has_cose_sig ? "This add-on is dual-signed (PKCS#7 and COSE)" : "This add-on is <strong>not</strong> signed with COSE"}. The PKCS#7 digest algorithm is: <strong>${pkcs7_algorithm}</strong>. ${has_cose_sig ? `The COSE algorithm is: <strong>${cose_algorithm}</strong>.` : ""}` : `❌ It doesn't appear to be signed.`}
+    ${prettySignature}
+    <ul>${verdict.reasons.map((reason) => `<li>${reason}</li>`).join("")}</ul>
         `;
 
-  if (is_enterprise) {
+  const icon = xpi.icon(64);
+  if (icon) {
+    // Icons are either PNG or SVG images.
+    const type = icon[0] === 0x89 ? "image/png" : "image/svg+xml";
+    const url = URL.createObjectURL(new Blob([icon], { type }));
+    $outputPretty.innerHTML =
+      `<img src="${url}" alt="" width="64" height="64"><br><br>` +
+      $outputPretty.innerHTML;
+  }
+
+  if (verdict.enterprise) {
     $outputPretty.innerHTML += `
-    <br>
-    <br>
     👔 This is an <strong>enterprise</strong> add-on.
     `;
   }
@@ -51,7 +73,15 @@ document.getElementById("input-file").addEventListener(
     const { files } = event.target;
     const reader = new FileReader();
     reader.onload = function (e) {
-      const xpi = new xpidump.XPI(new Uint8Array(reader.result));
+      let xpi;
+      try {
+        xpi = new xpidump.XPI(new Uint8Array(reader.result));
+      } catch (error) {
+        document.getElementById("output-pretty").textContent =
+          `⚠️ ${error.message}`;
+        document.getElementById("output-raw").textContent = "";
+        return;
+      }
 
       updateUI(xpi);
     };